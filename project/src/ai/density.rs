@@ -0,0 +1,196 @@
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use super::history::ShotHistory;
+use super::ShotStrategy;
+use crate::board::ShotResult;
+use crate::bot::BotError;
+
+// Expert computer opponent. Every turn it counts, for each unknown cell, how many
+// legal placements of the ships still afloat would cover it, and fires at the
+// cell with the highest count. Ties are broken with the caller's RNG, so a
+// seeded RNG gives the same game every time.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DensityAi;
+
+impl ShotStrategy for DensityAi {
+    fn choose_target(&mut self, history: &ShotHistory, rng: &mut dyn RngCore) -> Result<(usize, usize), BotError> {
+        // While there are unresolved hits only placements through them are worth counting
+        let mut density = self.density(history, !history.open_hits().is_empty());
+        if density.iter().flatten().all(|&count| count == 0) {
+            density = self.density(history, false);
+        }
+
+        let unknown = history.unknown_cells();
+        let best = unknown
+            .iter()
+            .map(|&(row, col)| density[row][col])
+            .max()
+            .expect("no cells left to fire at");
+        let candidates: Vec<(usize, usize)> = unknown
+            .into_iter()
+            .filter(|&(row, col)| density[row][col] == best)
+            .collect();
+
+        Ok(*candidates.choose(rng).expect("no cells left to fire at"))
+    }
+}
+
+impl DensityAi {
+    // Placement counts for every cell of the board
+    pub fn density(&self, history: &ShotHistory, through_hits_only: bool) -> Vec<Vec<u32>> {
+        let mut counts = vec![vec![0; history.width()]; history.height()];
+
+        for ship in history.remaining_ships() {
+            for horizontal in [true, false] {
+                for row in 0..history.height() {
+                    for col in 0..history.width() {
+                        let weight = Self::placement_weight(history, ship.size(), row, col, horizontal, through_hits_only);
+                        if weight == 0 {
+                            continue;
+                        }
+
+                        for i in 0..ship.size() {
+                            let (r, c) = if horizontal { (row, col + i) } else { (row + i, col) };
+                            if history.is_unknown(r, c) {
+                                counts[r][c] += weight;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        counts
+    }
+
+    // 0 if the placement contradicts what is known, otherwise how much it should count.
+    // Placements running through several unresolved hits are the most likely ones.
+    fn placement_weight(history: &ShotHistory, size: usize, row: usize, col: usize, horizontal: bool, through_hits_only: bool) -> u32 {
+        let fits = if horizontal {
+            col + size <= history.width()
+        } else {
+            row + size <= history.height()
+        };
+        if !fits {
+            return 0;
+        }
+
+        let mut covered_hits = 0;
+        for i in 0..size {
+            let (r, c) = if horizontal { (row, col + i) } else { (row + i, col) };
+            match history.result_at(r, c) {
+                None => {}
+                Some(ShotResult::Hit(_)) if history.is_open_hit((r, c)) => covered_hits += 1,
+                // Misses and cells of ships already sunk cannot hold another ship
+                Some(_) => return 0,
+            }
+        }
+
+        if through_hits_only {
+            covered_hits
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::HuntTargetAi;
+    use crate::board::{Board, LayoutStyle};
+    use crate::fleet::Fleet;
+    use crate::rng::MatchRng;
+    use crate::rules::RuleSet;
+    use crate::ship::{Orientation, ShipType};
+
+    // Plays the strategy against a fixed layout until every ship is sunk
+    fn shots_to_sink_all(seed: u64) -> Vec<(usize, usize)> {
+        let rules = RuleSet::classic();
+        let mut board = Board::new(rules.width(), rules.height());
+        board.place(ShipType::Carrier, (0, 0), Orientation::Horizontal).unwrap();
+        board.place(ShipType::Battleship, (9, 6), Orientation::Horizontal).unwrap();
+        board.place(ShipType::Cruiser, (3, 4), Orientation::Vertical).unwrap();
+        board.place(ShipType::Submarine, (5, 8), Orientation::Vertical).unwrap();
+        board.place(ShipType::Destroyer, (7, 1), Orientation::Horizontal).unwrap();
+
+        let mut history = ShotHistory::new(&rules);
+        let mut rng = MatchRng::new(seed);
+        let mut shots = Vec::new();
+        while !board.all_sunk() {
+            let (row, col) = DensityAi.choose_target(&history, &mut rng).unwrap();
+            history.record(row, col, board.fire(row, col).expect("fired at the same cell twice"));
+            shots.push((row, col));
+        }
+        shots
+    }
+
+    // Layouts the average shots to win are measured over, seeded 0 and on
+    const AVERAGE_LAYOUTS: u64 = 200;
+    // Most shots the density search may need on average before it counts as a regression
+    const MAX_AVERAGE_SHOTS: f64 = 48.0;
+
+    // Mean shots `strategy` needs to sink random classic fleets, each layout and each
+    // search seeded, so it comes out the same every run
+    fn average_shots_to_win(strategy: &mut dyn ShotStrategy) -> f64 {
+        let rules = RuleSet::classic();
+        let mut total = 0;
+        for seed in 0..AVERAGE_LAYOUTS {
+            let mut rng = MatchRng::new(seed);
+            let mut fleet = Fleet::new(&rules);
+            assert!(fleet.place_remaining(LayoutStyle::Random, &mut rng));
+            let mut board = fleet.into_board();
+
+            let mut history = ShotHistory::new(&rules);
+            while !board.all_sunk() {
+                let (row, col) = strategy.choose_target(&history, &mut rng).unwrap();
+                history.record(row, col, board.fire(row, col).expect("fired at the same cell twice"));
+                total += 1;
+            }
+        }
+        total as f64 / AVERAGE_LAYOUTS as f64
+    }
+
+    #[test]
+    fn average_shots_to_win_beats_hunt_and_target() {
+        let density = average_shots_to_win(&mut DensityAi);
+        assert_eq!(density, average_shots_to_win(&mut DensityAi));
+        // Recorded from the current search, like `same_seed_plays_the_same_shots`. A
+        // better search may lower it, but it must never climb past the limit.
+        assert_eq!(density, 45.755);
+        assert!(density < MAX_AVERAGE_SHOTS, "{} shots on average", density);
+
+        // Hunt and target takes about 50, so the density search should stay well ahead
+        let hunt_target = average_shots_to_win(&mut HuntTargetAi);
+        assert!(density + 2.0 < hunt_target, "{} shots against {} for hunt and target", density, hunt_target);
+    }
+
+    #[test]
+    fn first_shot_is_in_the_middle() {
+        let rules = RuleSet::classic();
+        let history = ShotHistory::new(&rules);
+        let (row, col) = DensityAi.choose_target(&history, &mut MatchRng::new(1)).unwrap();
+        assert!((4..=5).contains(&row) && (4..=5).contains(&col), "fired at {},{}", row, col);
+    }
+
+    #[test]
+    fn follows_up_next_to_a_hit() {
+        let rules = RuleSet::classic();
+        let mut history = ShotHistory::new(&rules);
+        history.record(4, 4, ShotResult::Hit(ShipType::Cruiser));
+
+        let (row, col) = DensityAi.choose_target(&history, &mut MatchRng::new(1)).unwrap();
+        assert_eq!(row.abs_diff(4) + col.abs_diff(4), 1, "fired at {},{}", row, col);
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_shots() {
+        let shots = shots_to_sink_all(42);
+        assert_eq!(shots, shots_to_sink_all(42));
+        // Recorded from the current search. A change here means seeded matches and
+        // saved games against the Hard AI play out differently than before.
+        assert_eq!(shots[..3], [(4, 5), (5, 4), (4, 4)]);
+        assert_eq!(shots.len(), 56);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::board::ShotResult;
+use crate::rules::RuleSet;
+use crate::ship::ShipType;
+
+pub const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+// Everything the computer has learned from its own shots, plus the board size and
+// fleet it is looking for. It never holds the positions of the opponent's ships,
+// only the answers it got back.
+#[derive(Clone, Debug)]
+pub struct ShotHistory {
+    width: usize,
+    height: usize,
+    fleet: Vec<ShipType>,
+    shots: Vec<Vec<Option<ShotResult>>>,
+    // Cells fired at in the current salvo whose results have not been revealed yet
+    pending: Vec<(usize, usize)>,
+    // Hits that do not belong to a ship known to be sunk, in the order they were made
+    open_hits: Vec<(usize, usize)>,
+    sunk_ships: Vec<ShipType>,
+    // Ships cannot touch, so the cells around a sunk ship are known to be water
+    no_touching: bool,
+}
+
+impl ShotHistory {
+    pub fn new(rules: &RuleSet) -> Self {
+        Self {
+            width: rules.width(),
+            height: rules.height(),
+            fleet: rules.fleet().to_vec(),
+            shots: vec![vec![None; rules.width()]; rules.height()],
+            pending: Vec::new(),
+            open_hits: Vec::new(),
+            sunk_ships: Vec::new(),
+            no_touching: rules.no_touching(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Moves `distance` cells from (row, col) in direction `dir`, or None if that leaves the board
+    pub fn step(&self, row: usize, col: usize, (dr, dc): (isize, isize), distance: usize) -> Option<(usize, usize)> {
+        let r = row as isize + dr * distance as isize;
+        let c = col as isize + dc * distance as isize;
+        if r >= 0 && r < self.height as isize && c >= 0 && c < self.width as isize {
+            Some((r as usize, c as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn result_at(&self, row: usize, col: usize) -> Option<ShotResult> {
+        self.shots[row][col]
+    }
+
+    // Not fired at yet, not even as part of the salvo being put together
+    pub fn is_unknown(&self, row: usize, col: usize) -> bool {
+        self.shots[row][col].is_none() && !self.pending.contains(&(row, col))
+    }
+
+    pub fn is_open_hit(&self, cell: (usize, usize)) -> bool {
+        self.open_hits.contains(&cell)
+    }
+
+    pub fn open_hits(&self) -> &[(usize, usize)] {
+        &self.open_hits
+    }
+
+    pub fn sunk_ships(&self) -> &[ShipType] {
+        &self.sunk_ships
+    }
+
+    // The opponent's ships that have not been reported sunk yet
+    pub fn remaining_ships(&self) -> Vec<ShipType> {
+        let mut remaining = self.fleet.clone();
+        for sunk in &self.sunk_ships {
+            if let Some(index) = remaining.iter().position(|ship| ship == sunk) {
+                remaining.remove(index);
+            }
+        }
+        remaining
+    }
+
+    pub fn unknown_cells(&self) -> Vec<(usize, usize)> {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.is_unknown(row, col))
+            .collect()
+    }
+
+    // How many cells are still unknown, counting those reserved for the salvo being
+    // put together
+    pub fn unresolved_cells(&self) -> usize {
+        self.shots.iter().flatten().filter(|shot| shot.is_none()).count()
+    }
+
+    // Reserves a cell for the salvo being put together so it is not picked twice
+    pub fn mark_pending(&mut self, row: usize, col: usize) {
+        self.pending.push((row, col));
+    }
+
+    pub fn record(&mut self, row: usize, col: usize, result: ShotResult) {
+        self.pending.retain(|&cell| cell != (row, col));
+        self.shots[row][col] = Some(result);
+
+        match result {
+            ShotResult::Miss => {}
+            ShotResult::Hit(_) => self.open_hits.push((row, col)),
+            ShotResult::Sunk(ship) => {
+                self.sunk_ships.push(ship);
+                self.resolve_sunk(row, col, ship.size());
+            }
+        }
+    }
+
+    // Records every unknown cell around `cells` as a miss without firing at it
+    fn mark_water_around(&mut self, cells: &[(usize, usize)]) {
+        for &(row, col) in cells {
+            for r in row.saturating_sub(1)..=(row + 1).min(self.height - 1) {
+                for c in col.saturating_sub(1)..=(col + 1).min(self.width - 1) {
+                    if self.is_unknown(r, c) {
+                        self.shots[r][c] = Some(ShotResult::Miss);
+                    }
+                }
+            }
+        }
+    }
+
+    // Drops the sunk ship's cells from the open hits: the sinking shot plus the
+    // line of earlier hits running off from it in one direction.
+    fn resolve_sunk(&mut self, row: usize, col: usize, size: usize) {
+        for dir in DIRECTIONS {
+            let line: Option<Vec<(usize, usize)>> = (1..size)
+                .map(|distance| self.step(row, col, dir, distance).filter(|&cell| self.is_open_hit(cell)))
+                .collect();
+
+            if let Some(mut line) = line {
+                self.open_hits.retain(|cell| !line.contains(cell));
+                if self.no_touching {
+                    line.push((row, col));
+                    self.mark_water_around(&line);
+                }
+                return;
+            }
+        }
+    }
+}
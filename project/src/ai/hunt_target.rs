@@ -0,0 +1,76 @@
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use super::history::{ShotHistory, DIRECTIONS};
+use super::ShotStrategy;
+use crate::bot::BotError;
+
+// Hunts with random shots until something is hit, then targets the cells
+// around the hit and along the ship's axis until it is sunk.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct HuntTargetAi;
+
+impl ShotStrategy for HuntTargetAi {
+    fn choose_target(&mut self, history: &ShotHistory, rng: &mut dyn RngCore) -> Result<(usize, usize), BotError> {
+        Ok(Self::target_cell(history).unwrap_or_else(|| Self::hunt_cell(history, rng)))
+    }
+}
+
+impl HuntTargetAi {
+    // Random unknown cell. Only every n-th diagonal needs searching, where n is the
+    // length of the smallest ship still afloat, as no ship can fit between them.
+    fn hunt_cell(history: &ShotHistory, rng: &mut dyn RngCore) -> (usize, usize) {
+        let spacing = history.remaining_ships().iter().map(|ship| ship.size()).min().unwrap_or(1);
+        let unknown = history.unknown_cells();
+        let parity: Vec<(usize, usize)> = unknown
+            .iter()
+            .copied()
+            .filter(|&(row, col)| (row + col) % spacing == 0)
+            .collect();
+
+        let candidates = if parity.is_empty() { &unknown } else { &parity };
+        *candidates.choose(rng).expect("no cells left to fire at")
+    }
+
+    fn target_cell(history: &ShotHistory) -> Option<(usize, usize)> {
+        // Once two neighbouring hits show the ship's axis, keep walking along it
+        for &(row, col) in history.open_hits() {
+            for axis in [(0, 1), (1, 0)] {
+                let backwards = (-axis.0, -axis.1);
+                let on_axis = [axis, backwards]
+                    .iter()
+                    .any(|&dir| history.step(row, col, dir, 1).is_some_and(|cell| history.is_open_hit(cell)));
+                if !on_axis {
+                    continue;
+                }
+
+                for dir in [axis, backwards] {
+                    let mut distance = 1;
+                    while let Some(cell) = history.step(row, col, dir, distance) {
+                        if history.is_open_hit(cell) {
+                            distance += 1;
+                        } else {
+                            if history.is_unknown(cell.0, cell.1) {
+                                return Some(cell);
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Otherwise probe around a single hit
+        for &(row, col) in history.open_hits() {
+            for dir in DIRECTIONS {
+                if let Some((r, c)) = history.step(row, col, dir, 1) {
+                    if history.is_unknown(r, c) {
+                        return Some((r, c));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
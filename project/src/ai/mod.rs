@@ -0,0 +1,47 @@
+mod density;
+mod history;
+mod hunt_target;
+mod random;
+
+pub use density::DensityAi;
+pub use history::ShotHistory;
+pub use hunt_target::HuntTargetAi;
+pub use random::RandomAi;
+
+use rand::RngCore;
+
+use crate::bot::BotError;
+
+// How the computer picks its next shot. A strategy only ever sees the history
+// of its own shots and their outcomes, never the opponent's board, so it has
+// no way of cheating. The returned cell must not have been fired at yet.
+pub trait ShotStrategy {
+    // Only an external bot can fail to pick a cell, e.g. when its program stops
+    // answering. The built in strategies always pick one.
+    fn choose_target(&mut self, history: &ShotHistory, rng: &mut dyn RngCore) -> Result<(usize, usize), BotError>;
+
+    // Whether the next `count` targets can be picked without waiting, or the pick would
+    // fail straight away. Only an external bot ever has to wait, for its program.
+    fn is_ready(&mut self, _history: &ShotHistory, _count: usize) -> bool {
+        true
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,   // Random shots
+    Medium, // Hunt and target
+    Hard,   // Probability density
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn strategy(&self) -> Box<dyn ShotStrategy> {
+        match self {
+            Difficulty::Easy => Box::new(RandomAi),
+            Difficulty::Medium => Box::new(HuntTargetAi),
+            Difficulty::Hard => Box::new(DensityAi),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use super::history::ShotHistory;
+use super::ShotStrategy;
+use crate::bot::BotError;
+
+// Fires at a uniformly random cell it has not tried yet
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RandomAi;
+
+impl ShotStrategy for RandomAi {
+    fn choose_target(&mut self, history: &ShotHistory, rng: &mut dyn RngCore) -> Result<(usize, usize), BotError> {
+        Ok(*history.unknown_cells().choose(rng).expect("no cells left to fire at"))
+    }
+}
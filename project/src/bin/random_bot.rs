@@ -0,0 +1,114 @@
+// A bot for the external bot protocol that lays out its fleet at random and fires at
+// random cells. It only uses the protocol, as a bot written in another language would,
+// and is a starting point for writing one, e.g.
+//
+//     cargo build --release --bin random_bot
+//     cargo run --release --bin simulate -- --first-bot target/release/random_bot
+use std::io::{self, BufRead, Write};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const SHIP_SIZES: [(&str, usize); 7] = [
+    ("Carrier", 5),
+    ("Battleship", 4),
+    ("Cruiser", 3),
+    ("Submarine", 3),
+    ("Destroyer", 2),
+    ("PatrolBoat", 2),
+    ("TorpedoBoat", 1),
+];
+
+// Random layouts tried before giving up on the fleet
+const MAX_LAYOUT_ATTEMPTS: usize = 100_000;
+
+struct Rules {
+    width: usize,
+    height: usize,
+    no_touching: bool,
+    fleet: Vec<(String, usize)>,
+}
+
+// "rules 10 10 Classic false Carrier,Battleship,Cruiser,Submarine,Destroyer Classic"
+fn parse_rules(value: &str) -> Option<Rules> {
+    let fields: Vec<_> = value.split_whitespace().collect();
+    let fleet = fields.get(4)?.split(',').map(|name| {
+        let size = SHIP_SIZES.iter().find(|(known, _)| *known == name).map_or(1, |&(_, size)| size);
+        (name.to_string(), size)
+    });
+    Some(Rules {
+        width: fields.first()?.parse().ok()?,
+        height: fields.get(1)?.parse().ok()?,
+        no_touching: *fields.get(3)? == "true",
+        fleet: fleet.collect(),
+    })
+}
+
+// The fleet as "Ship,row,col,horizontal" placements, or None if no layout was found
+fn place_fleet(rules: &Rules, rng: &mut impl Rng) -> Option<Vec<String>> {
+    'attempt: for _ in 0..MAX_LAYOUT_ATTEMPTS {
+        let mut occupied = vec![vec![false; rules.width]; rules.height];
+        let mut placements = Vec::new();
+        for (name, size) in &rules.fleet {
+            let horizontal = rng.gen_bool(0.5);
+            let (rows, cols) = if horizontal { (1, *size) } else { (*size, 1) };
+            if rows > rules.height || cols > rules.width {
+                continue 'attempt;
+            }
+            let row = rng.gen_range(0..=rules.height - rows);
+            let col = rng.gen_range(0..=rules.width - cols);
+
+            // Without touching, the ring of cells around the ship must be free as well
+            let margin = usize::from(rules.no_touching);
+            let blocked = (row.saturating_sub(margin)..(row + rows + margin).min(rules.height)).any(|r| {
+                (col.saturating_sub(margin)..(col + cols + margin).min(rules.width)).any(|c| occupied[r][c])
+            });
+            if blocked {
+                continue 'attempt;
+            }
+            for cells in &mut occupied[row..row + rows] {
+                cells[col..col + cols].fill(true);
+            }
+            let orientation = if horizontal { "horizontal" } else { "vertical" };
+            placements.push(format!("{},{},{},{}", name, row, col, orientation));
+        }
+        return Some(placements);
+    }
+    None
+}
+
+fn main() -> io::Result<()> {
+    let mut rng = rand::thread_rng();
+    let mut output = io::stdout().lock();
+    let mut rules = None;
+    let mut targets = Vec::new();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let (command, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        match command {
+            "battleship" => writeln!(output, "id name Random\nok 1")?,
+            "rules" => {
+                rules = parse_rules(rest);
+                if let Some(rules) = &rules {
+                    targets = (0..rules.height).flat_map(|row| (0..rules.width).map(move |col| (row, col))).collect();
+                    targets.shuffle(&mut rng);
+                }
+            }
+            "place" => {
+                let placements = rules.as_ref().and_then(|rules| place_fleet(rules, &mut rng)).unwrap_or_default();
+                writeln!(output, "fleet {}", placements.join(" "))?;
+            }
+            "go" => match targets.pop() {
+                Some((row, col)) => writeln!(output, "fire {},{}", row, col)?,
+                None => writeln!(output, "info out of cells")?,
+            },
+            // A random bot learns nothing from its shots
+            "result" => {}
+            "quit" => break,
+            _ => writeln!(output, "info unknown command {}", command)?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,182 @@
+// Plays many matches between two computer players without opening a window and
+// prints how they went, e.g.
+//
+//     cargo run --release --bin simulate -- --games 10000 --first hard --second medium
+use std::fmt::Debug;
+use std::process;
+use std::thread;
+
+use battleship::{simulate, Contestant, Difficulty, GameMode, LayoutStyle, MatchReport, RuleSet, ShotStats, Side};
+
+const USAGE: &str = "Usage: simulate [options]
+  --games <n>              matches to play (default 1000)
+  --first <difficulty>     easy, medium or hard (default hard)
+  --second <difficulty>    easy, medium or hard (default medium)
+  --first-layout <style>   random or spreadout (default random)
+  --second-layout <style>  random or spreadout (default random)
+  --first-bot <command>    an external bot plays first instead, laying out its own fleet
+  --second-bot <command>   an external bot plays second instead
+  --rules <preset>         classic, russian or the name of another preset (default classic)
+  --salvo                  one shot per ship still afloat
+  --no-touching            ships may not touch each other
+  --seed <n>               seed of the first match, the others follow on (default random)
+  --threads <n>            matches played at once (default one per core)
+  --bucket <n>             shots per histogram bar (default 5)";
+
+// Longest bar of the histogram, in characters
+const HISTOGRAM_WIDTH: usize = 50;
+
+struct Options {
+    games: usize,
+    first: Contestant,
+    second: Contestant,
+    rules: RuleSet,
+    seed: u64,
+    threads: usize,
+    bucket: usize,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        games: 1000,
+        first: Contestant::new(Difficulty::Hard),
+        second: Contestant::new(Difficulty::Medium),
+        rules: RuleSet::classic(),
+        seed: rand::random(),
+        threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        bucket: 5,
+    };
+    let mut first = (Difficulty::Hard, LayoutStyle::Random, None);
+    let mut second = (Difficulty::Medium, LayoutStyle::Random, None);
+    let (mut mode, mut no_touching) = (GameMode::Classic, false);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--games" => options.games = parse_number(&value()?)?,
+            "--first" => first.0 = parse_name(&Difficulty::ALL, &value()?)?,
+            "--second" => second.0 = parse_name(&Difficulty::ALL, &value()?)?,
+            "--first-layout" => first.1 = parse_name(&LayoutStyle::ALL, &value()?)?,
+            "--second-layout" => second.1 = parse_name(&LayoutStyle::ALL, &value()?)?,
+            "--first-bot" => first.2 = Some(value()?),
+            "--second-bot" => second.2 = Some(value()?),
+            "--rules" => {
+                let name = value()?;
+                options.rules = RuleSet::presets()
+                    .into_iter()
+                    .find(|rules| rules.name().eq_ignore_ascii_case(&name))
+                    .ok_or(format!("there are no {} rules", name))?;
+            }
+            "--salvo" => mode = GameMode::Salvo,
+            "--no-touching" => no_touching = true,
+            "--seed" => options.seed = parse_number(&value()?)?,
+            "--threads" => options.threads = parse_number(&value()?)?,
+            "--bucket" => options.bucket = parse_number(&value()?)?,
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    options.first = contestant(first);
+    options.second = contestant(second);
+    options.rules = options.rules.with_mode(mode).with_no_touching(no_touching);
+    Ok(options)
+}
+
+fn contestant((difficulty, layout, bot): (Difficulty, LayoutStyle, Option<String>)) -> Contestant {
+    match bot {
+        Some(command) => Contestant::external(&command),
+        None => Contestant::new(difficulty).with_layout(layout),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} is not a whole number", value))
+}
+
+// Enum values are given by their Debug names, in any case
+fn parse_name<T: Copy + Debug>(all: &[T], name: &str) -> Result<T, String> {
+    all.iter()
+        .copied()
+        .find(|item| format!("{:?}", item).eq_ignore_ascii_case(name))
+        .ok_or(format!("{} is not one of {:?}", name, all))
+}
+
+fn describe(contestant: &Contestant) -> String {
+    match contestant.difficulty() {
+        Some(difficulty) => format!("{:?} AI with a {:?} layout", difficulty, contestant.layout()),
+        None => format!("external bot {}", contestant.command().unwrap_or_default()),
+    }
+}
+
+fn print_side(name: &str, contestant: &Contestant, reports: &[MatchReport], side: Side, bucket: usize) {
+    let stats = ShotStats::for_side(reports, side);
+    let wins = reports.iter().filter(|report| report.winner() == side).count();
+    let win_rate = 100.0 * wins as f64 / reports.len().max(1) as f64;
+    println!();
+    println!("{}: {}", name, describe(contestant));
+    println!("  Wins: {} ({:.1}%)", wins, win_rate);
+    if contestant.command().is_some() {
+        // Matches the bot lost by failing during them rather than by being sunk
+        let forfeits = reports.iter().filter(|report| report.is_forfeit() && report.winner() != side).count();
+        println!("  Forfeits: {}", forfeits);
+    }
+
+    let (Some(mean), Some(median)) = (stats.mean(), stats.median()) else {
+        return;
+    };
+    println!(
+        "  Shots to win: mean {:.1}, median {:.1}, min {}, max {}",
+        mean,
+        median,
+        stats.min().unwrap_or(0),
+        stats.max().unwrap_or(0)
+    );
+    let percentiles: Vec<_> = [10.0, 25.0, 75.0, 90.0, 99.0]
+        .iter()
+        .filter_map(|&percent| Some(format!("p{} {:.1}", percent, stats.percentile(percent)?)))
+        .collect();
+    println!("  Percentiles: {}", percentiles.join(", "));
+
+    let histogram = stats.histogram(bucket);
+    let most = histogram.iter().map(|&(_, count)| count).max().unwrap_or(1);
+    for (low, count) in histogram {
+        let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(most));
+        println!("  {:>4}-{:<4} {:>7} {}", low, low + bucket.max(1) - 1, count, bar);
+    }
+}
+
+fn main() {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let rules = &options.rules;
+    println!(
+        "{} games of {} rules ({:?}{}), seeds {} and on, {} threads",
+        options.games,
+        rules.name(),
+        rules.mode(),
+        if rules.no_touching() { ", no touching" } else { "" },
+        options.seed,
+        options.threads
+    );
+
+    let reports = match simulate(rules, &options.first, &options.second, options.games, options.seed, options.threads) {
+        Ok(reports) => reports,
+        Err(error) => {
+            eprintln!("Could not finish the simulation: {}", error);
+            process::exit(1);
+        }
+    };
+    let starter_wins = reports.iter().filter(|report| report.winner_started()).count();
+    let turns = reports.iter().map(MatchReport::turns).sum::<usize>() as f64 / reports.len().max(1) as f64;
+    println!("Won by the side that fired first: {:.1}%", 100.0 * starter_wins as f64 / reports.len().max(1) as f64);
+    println!("Turns per match: {:.1}", turns);
+
+    print_side("First", &options.first, &reports, Side::First, options.bucket);
+    print_side("Second", &options.second, &reports, Side::Second, options.bucket);
+}
@@ -0,0 +1,422 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::rules::DEFAULT_BOARD_SIZE;
+use crate::ship::{Orientation, PlacedShip, ShipId, ShipType};
+
+// Score added for every ship cell that has another ship next to it
+const TOUCHING_PENALTY: u32 = 50;
+
+// How a fleet is laid out when it is placed automatically
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LayoutStyle {
+    Random,
+    // Ships kept out of the crowded middle of the board and apart from each other,
+    // where a probability density search looks first
+    SpreadOut,
+}
+
+impl LayoutStyle {
+    pub const ALL: [LayoutStyle; 2] = [LayoutStyle::Random, LayoutStyle::SpreadOut];
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CellState {
+    Empty,
+    Occupied,
+    Hit,
+    Miss,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShotResult {
+    Miss,
+    Hit(ShipType),
+    Sunk(ShipType),
+}
+
+impl ShotResult {
+    pub fn is_hit(&self) -> bool {
+        !matches!(self, ShotResult::Miss)
+    }
+}
+
+impl fmt::Display for ShotResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShotResult::Miss => write!(f, "miss"),
+            ShotResult::Hit(ship) => write!(f, "hit {:?}", ship),
+            ShotResult::Sunk(ship) => write!(f, "sunk {:?}", ship),
+        }
+    }
+}
+
+// Why a ship cannot go where it was put
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PlacementError {
+    // Part of the ship would be off the board
+    OutOfBounds,
+    // The ship would cover a cell another ship is on
+    Overlap,
+    // The ship would touch another ship where the rules forbid it
+    Adjacent,
+    // Every ship of this type in the fleet is on the board already
+    AlreadyPlaced,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlacementError::OutOfBounds => write!(f, "the ship does not fit on the board there"),
+            PlacementError::Overlap => write!(f, "the ship would overlap another ship"),
+            PlacementError::Adjacent => write!(f, "the ship would touch another ship"),
+            PlacementError::AlreadyPlaced => write!(f, "every ship of that type is already placed"),
+        }
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<CellState>>,
+    // Index into `ships` for every occupied cell
+    ship_ids: Vec<Vec<Option<usize>>>,
+    ships: Vec<PlacedShip>,
+    // Every cell fired at, in the order of the shots
+    shots: Vec<(usize, usize)>,
+    // Ships may not touch each other, not even diagonally
+    no_touching: bool,
+}
+
+impl Board {
+    // `width` columns by `height` rows, all water
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![vec![CellState::Empty; width]; height],
+            ship_ids: vec![vec![None; width]; height],
+            ships: Vec::new(),
+            shots: Vec::new(),
+            no_touching: false,
+        }
+    }
+
+    pub fn with_no_touching(mut self, no_touching: bool) -> Self {
+        self.no_touching = no_touching;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, row: usize, col: usize) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> CellState {
+        self.cells[row][col]
+    }
+
+    // Whether (row, col) has not been fired at yet
+    pub fn can_fire(&self, row: usize, col: usize) -> bool {
+        matches!(self.cells[row][col], CellState::Empty | CellState::Occupied)
+    }
+
+    pub fn unfired_cells(&self) -> usize {
+        self.cells.iter().flatten().filter(|&&cell| matches!(cell, CellState::Empty | CellState::Occupied)).count()
+    }
+
+    pub fn ships(&self) -> &[PlacedShip] {
+        &self.ships
+    }
+
+    pub fn shots(&self) -> &[(usize, usize)] {
+        &self.shots
+    }
+
+    pub fn ship_at(&self, row: usize, col: usize) -> Option<&PlacedShip> {
+        self.ship_ids[row][col].map(|id| &self.ships[id])
+    }
+
+    pub fn all_sunk(&self) -> bool {
+        !self.ships.is_empty() && self.ships.iter().all(|ship| ship.is_sunk())
+    }
+
+    // Cells a ship would cover, without any bounds checking
+    fn ship_cells(
+        ship: ShipType,
+        (row, col): (usize, usize),
+        orientation: Orientation,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        (0..ship.size()).map(move |i| match orientation {
+            Orientation::Horizontal => (row, col + i),
+            Orientation::Vertical => (row + i, col),
+        })
+    }
+
+    // The up to eight cells around (row, col) that are on the board
+    pub fn neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut neighbours = Vec::with_capacity(8);
+        for r in row.saturating_sub(1)..=row + 1 {
+            for c in col.saturating_sub(1)..=col + 1 {
+                if (r, c) != (row, col) && self.in_bounds(r, c) {
+                    neighbours.push((r, c));
+                }
+            }
+        }
+        neighbours
+    }
+
+    // Whether `ship` fits with its bow at `origin`, and if not, why
+    pub fn check_placement(
+        &self,
+        ship: ShipType,
+        origin: (usize, usize),
+        orientation: Orientation,
+    ) -> Result<(), PlacementError> {
+        // The bow is checked first so working out the other cells cannot overflow
+        if !self.in_bounds(origin.0, origin.1) {
+            return Err(PlacementError::OutOfBounds);
+        }
+        let cells: Vec<_> = Self::ship_cells(ship, origin, orientation).collect();
+        if !cells.iter().all(|&(r, c)| self.in_bounds(r, c)) {
+            return Err(PlacementError::OutOfBounds);
+        }
+
+        let has_ship = |&(r, c): &(usize, usize)| self.ship_ids[r][c].is_some();
+        if cells.iter().any(has_ship) {
+            return Err(PlacementError::Overlap);
+        }
+        if self.no_touching && cells.iter().any(|&(r, c)| self.neighbours(r, c).iter().any(has_ship)) {
+            return Err(PlacementError::Adjacent);
+        }
+        Ok(())
+    }
+
+    pub fn can_place(&self, ship: ShipType, origin: (usize, usize), orientation: Orientation) -> bool {
+        self.check_placement(ship, origin, orientation).is_ok()
+    }
+
+    // Puts `ship` on the board with its bow at `origin`. Nothing changes if it does not fit.
+    pub fn place(
+        &mut self,
+        ship: ShipType,
+        origin: (usize, usize),
+        orientation: Orientation,
+    ) -> Result<ShipId, PlacementError> {
+        self.check_placement(ship, origin, orientation)?;
+
+        let id = self.ships.len();
+        let cells: Vec<_> = Self::ship_cells(ship, origin, orientation).collect();
+        for &(r, c) in &cells {
+            self.cells[r][c] = CellState::Occupied;
+            self.ship_ids[r][c] = Some(id);
+        }
+        self.ships.push(PlacedShip::new(ship, cells));
+        Ok(ShipId::new(id))
+    }
+
+    // Takes the ship covering (row, col) off the board. Only possible before any shots
+    // were fired at the board.
+    pub fn remove_ship(&mut self, row: usize, col: usize) -> Option<PlacedShip> {
+        if !self.in_bounds(row, col) || !self.shots.is_empty() {
+            return None;
+        }
+
+        let id = self.ship_ids[row][col]?;
+        let ship = self.ships.remove(id);
+        for &(r, c) in ship.cells() {
+            self.cells[r][c] = CellState::Empty;
+            self.ship_ids[r][c] = None;
+        }
+        // Ships after the removed one moved down by one
+        for ship_id in self.ship_ids.iter_mut().flatten().flatten() {
+            if *ship_id > id {
+                *ship_id -= 1;
+            }
+        }
+        Some(ship)
+    }
+
+    // Takes the ships placed after the first `count` back off the board
+    pub(crate) fn truncate_ships(&mut self, count: usize) {
+        for ship in self.ships.drain(count..) {
+            for &(r, c) in ship.cells() {
+                self.cells[r][c] = CellState::Empty;
+                self.ship_ids[r][c] = None;
+            }
+        }
+    }
+
+    // For every cell, how many placements of `ships` on this board would cover it. This is
+    // what a probability density search starts from.
+    pub(crate) fn coverage(&self, ships: &[ShipType]) -> Vec<Vec<u32>> {
+        let mut counts = vec![vec![0; self.width]; self.height];
+        for &ship in ships {
+            for horizontal in [true, false] {
+                for row in 0..self.height {
+                    for col in 0..self.width {
+                        let orientation = Orientation::from_horizontal(horizontal);
+                        if !self.can_place(ship, (row, col), orientation) {
+                            continue;
+                        }
+                        for (r, c) in Self::ship_cells(ship, (row, col), orientation) {
+                            counts[r][c] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    // Lower is harder to find: ships on rarely covered cells, with no ship next to another
+    pub(crate) fn spread_score(&self, coverage: &[Vec<u32>]) -> u32 {
+        let mut score = 0;
+        for (id, ship) in self.ships.iter().enumerate() {
+            for &(row, col) in ship.cells() {
+                score += coverage[row][col];
+                let touching = self
+                    .neighbours(row, col)
+                    .iter()
+                    .any(|&(r, c)| self.ship_ids[r][c].is_some_and(|other| other != id));
+                if touching {
+                    score += TOUCHING_PENALTY;
+                }
+            }
+        }
+        score
+    }
+
+    // Any position where `ship` stays on the board, all equally likely, whether or not
+    // other ships are in the way. The ship must fit on the board at all.
+    pub(crate) fn random_position<R: Rng>(&self, ship: ShipType, rng: &mut R) -> ((usize, usize), Orientation) {
+        let horizontal_count = self.height * (self.width + 1).saturating_sub(ship.size());
+        let vertical_count = self.width * (self.height + 1).saturating_sub(ship.size());
+        let index = rng.gen_range(0..horizontal_count + vertical_count);
+        if index < horizontal_count {
+            let columns = self.width + 1 - ship.size();
+            ((index / columns, index % columns), Orientation::Horizontal)
+        } else {
+            let index = index - horizontal_count;
+            ((index / self.width, index % self.width), Orientation::Vertical)
+        }
+    }
+
+    // Marks a shot whose result someone else worked out, e.g. on a board whose ships
+    // are only known to the opponent. False if the cell cannot be fired at.
+    pub fn record_result(&mut self, row: usize, col: usize, result: ShotResult) -> bool {
+        if !self.in_bounds(row, col) || !self.can_fire(row, col) {
+            return false;
+        }
+
+        self.cells[row][col] = if result.is_hit() { CellState::Hit } else { CellState::Miss };
+        self.shots.push((row, col));
+        true
+    }
+
+    // None if the cell is off the board or was already fired at
+    pub fn fire(&mut self, row: usize, col: usize) -> Option<ShotResult> {
+        if !self.in_bounds(row, col) {
+            return None;
+        }
+
+        match self.cells[row][col] {
+            CellState::Empty => {
+                self.cells[row][col] = CellState::Miss;
+                self.shots.push((row, col));
+                Some(ShotResult::Miss)
+            }
+            CellState::Occupied => {
+                self.cells[row][col] = CellState::Hit;
+                self.shots.push((row, col));
+
+                let ship = &mut self.ships[self.ship_ids[row][col]?];
+                ship.take_hit();
+                if ship.is_sunk() {
+                    Some(ShotResult::Sunk(ship.ship_type()))
+                } else {
+                    Some(ShotResult::Hit(ship.ship_type()))
+                }
+            }
+            CellState::Hit | CellState::Miss => None,
+        }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_covers_the_ship_cells() {
+        let mut board = Board::new(10, 10);
+        board.place(ShipType::Cruiser, (2, 3), Orientation::Vertical).unwrap();
+
+        for row in 2..5 {
+            assert_eq!(board.cell(row, 3), CellState::Occupied);
+            assert_eq!(board.ship_at(row, 3).map(PlacedShip::ship_type), Some(ShipType::Cruiser));
+        }
+        assert_eq!(board.cell(5, 3), CellState::Empty);
+    }
+
+    #[test]
+    fn place_rejects_overlap_and_leaves_the_board_alone() {
+        let mut board = Board::new(10, 10);
+        board.place(ShipType::Carrier, (4, 0), Orientation::Horizontal).unwrap();
+
+        assert_eq!(board.place(ShipType::Destroyer, (3, 2), Orientation::Vertical), Err(PlacementError::Overlap));
+        assert_eq!(board.ships().len(), 1);
+        assert_eq!(board.cell(3, 2), CellState::Empty);
+    }
+
+    #[test]
+    fn place_rejects_ships_off_the_board() {
+        let mut board = Board::new(10, 10);
+        assert_eq!(board.place(ShipType::Carrier, (0, 6), Orientation::Horizontal), Err(PlacementError::OutOfBounds));
+        assert_eq!(board.place(ShipType::Carrier, (6, 0), Orientation::Vertical), Err(PlacementError::OutOfBounds));
+        assert_eq!(
+            board.place(ShipType::Destroyer, (usize::MAX, 0), Orientation::Vertical),
+            Err(PlacementError::OutOfBounds)
+        );
+        assert!(board.ships().is_empty());
+    }
+
+    #[test]
+    fn no_touching_rejects_diagonal_neighbours() {
+        let mut board = Board::new(10, 10).with_no_touching(true);
+        board.place(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+
+        assert_eq!(board.place(ShipType::Destroyer, (1, 2), Orientation::Horizontal), Err(PlacementError::Adjacent));
+        assert!(board.place(ShipType::Destroyer, (2, 2), Orientation::Horizontal).is_ok());
+    }
+
+    #[test]
+    fn fire_reports_hits_sinks_and_repeats() {
+        let mut board = Board::new(10, 10);
+        board.place(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+
+        assert_eq!(board.fire(5, 5), Some(ShotResult::Miss));
+        assert_eq!(board.fire(0, 0), Some(ShotResult::Hit(ShipType::Destroyer)));
+        assert_eq!(board.fire(0, 0), None);
+        assert_eq!(board.fire(0, 1), Some(ShotResult::Sunk(ShipType::Destroyer)));
+        assert_eq!(board.fire(10, 0), None);
+        assert!(board.all_sunk());
+    }
+}
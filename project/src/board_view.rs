@@ -0,0 +1,106 @@
+use ggez::graphics::{self, Color, DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use battleship::{Board, CellState, RuleSet};
+
+use crate::layout::Layout;
+
+const BORDER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+// What a board mesh was built from. The mesh is only built again when this changes.
+#[derive(PartialEq)]
+struct Snapshot {
+    cells: Vec<CellState>,
+    area: Rect,
+    show_ships: bool,
+}
+
+// Draws a board as a single mesh that is kept between frames, instead of two new
+// meshes for every cell
+#[derive(Default)]
+pub struct BoardView {
+    cached: Option<(Snapshot, Mesh)>,
+}
+
+impl BoardView {
+    // Draws `board` in `area`, with its ships showing if `show_ships` is set
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        layout: &Layout,
+        rules: &RuleSet,
+        board: &Board,
+        area: Rect,
+        show_ships: bool,
+    ) -> GameResult {
+        let snapshot = Snapshot {
+            cells: (0..board.height())
+                .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+                .map(|(row, col)| board.cell(row, col))
+                .collect(),
+            area,
+            show_ships,
+        };
+
+        let is_current = self.cached.as_ref().is_some_and(|(cached, _)| *cached == snapshot);
+        if !is_current {
+            let mesh = Self::build(ctx, layout, rules, board, area, show_ships)?;
+            self.cached = Some((snapshot, mesh));
+        }
+
+        match &self.cached {
+            Some((_, mesh)) => graphics::draw(ctx, mesh, graphics::DrawParam::default()),
+            None => Ok(()),
+        }
+    }
+
+    fn build(
+        ctx: &mut Context,
+        layout: &Layout,
+        rules: &RuleSet,
+        board: &Board,
+        area: Rect,
+        show_ships: bool,
+    ) -> GameResult<Mesh> {
+        let mut builder = MeshBuilder::new();
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                let cell = layout.cell(area, rules, row, col);
+                builder.rectangle(DrawMode::fill(), cell, cell_color(board.cell(row, col), show_ships))?;
+                builder.rectangle(DrawMode::stroke(1.0), cell, BORDER_COLOR)?;
+            }
+        }
+        builder.build(ctx)
+    }
+}
+
+fn cell_color(cell: CellState, show_ships: bool) -> Color {
+    match cell {
+        CellState::Occupied if show_ships => Color::from_rgb(100, 100, 100), // Dark grey for occupied cells
+        CellState::Empty | CellState::Occupied => Color::from_rgb(0, 128, 255), // Water
+        CellState::Hit => Color::from_rgb(255, 0, 0),
+        CellState::Miss => Color::from_rgb(255, 255, 255),
+    }
+}
+
+// Fills `cells` of `board` in one mesh, e.g. the marked salvo targets. Nothing is
+// drawn if there are no cells.
+pub fn draw_cells(
+    ctx: &mut Context,
+    layout: &Layout,
+    rules: &RuleSet,
+    board: Rect,
+    cells: &[(usize, usize)],
+    color: Color,
+) -> GameResult {
+    if cells.is_empty() {
+        return Ok(());
+    }
+
+    let mut builder = MeshBuilder::new();
+    for &(row, col) in cells {
+        builder.rectangle(DrawMode::fill(), layout.cell(board, rules, row, col), color)?;
+    }
+    let mesh = builder.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+}
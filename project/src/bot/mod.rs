@@ -0,0 +1,326 @@
+// Opponents written as programs of their own, in any language. The game starts the
+// program and talks to it over its standard input and output, one line at a time,
+// much like chess programs talk to a chess GUI over UCI.
+mod protocol;
+
+pub use protocol::{BotCommand, BotReply, BOT_PROTOCOL_VERSION};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+
+use crate::ai::{ShotHistory, ShotStrategy};
+use crate::fleet::Fleet;
+use crate::notation::cell_name;
+use crate::rules::RuleSet;
+use crate::save::SaveError;
+use crate::ship::Orientation;
+
+// How long a bot may take to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+// How long a bot gets to quit by itself before it is killed
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum BotError {
+    Io(io::Error),
+    // The bot said something this version does not understand, or not when it should have
+    Protocol(String),
+    VersionMismatch(u32),
+    // The bot's fleet is not a legal layout of the fleet the rules ask for
+    InvalidFleet(String),
+    // The bot fired somewhere it cannot
+    InvalidShot(usize, usize),
+    Timeout,
+    Exited,
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::Io(error) => write!(f, "{}", error),
+            BotError::Protocol(reason) => write!(f, "protocol error: {}", reason),
+            BotError::VersionMismatch(version) => {
+                write!(f, "the bot speaks protocol version {}, not {}", version, BOT_PROTOCOL_VERSION)
+            }
+            BotError::InvalidFleet(reason) => write!(f, "the bot's fleet breaks the rules: {}", reason),
+            BotError::InvalidShot(row, col) => write!(f, "the bot cannot fire at {},{}", row, col),
+            BotError::Timeout => write!(f, "the bot did not answer in time"),
+            BotError::Exited => write!(f, "the bot exited"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+impl From<io::Error> for BotError {
+    fn from(error: io::Error) -> Self {
+        BotError::Io(error)
+    }
+}
+
+impl From<SaveError> for BotError {
+    fn from(error: SaveError) -> Self {
+        BotError::Protocol(error.to_string())
+    }
+}
+
+// A running bot program. It plays the computer's seat, or the player's in simulated
+// matches, like any other strategy. A bot that breaks the protocol or stops answering
+// during a match forfeits it.
+pub struct ExternalBot {
+    name: String,
+    process: Child,
+    stdin: ChildStdin,
+    // Read on a background thread, so a bot that stops answering cannot hang the game
+    replies: Receiver<Result<BotReply, BotError>>,
+    rules: RuleSet,
+    // Every cell the bot has fired at, including those of the volley being fired
+    fired: Vec<Vec<bool>>,
+    // The bot's shots it has not been told the results of yet, in the order they were fired
+    unreported: Vec<(usize, usize)>,
+    // Go commands the bot has not answered yet, and when the oldest must be answered by
+    awaited: usize,
+    deadline: Instant,
+    // Shots the bot has answered with that are not picked yet
+    shots: VecDeque<(usize, usize)>,
+    // Why the bot failed while its shots were polled, for the next pick to return
+    failure: Option<BotError>,
+}
+
+impl ExternalBot {
+    // Starts `command`, a program followed by its arguments separated by spaces, and
+    // tells it the rules of the match
+    pub fn launch(command: &str, rules: &RuleSet) -> Result<Self, BotError> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or_else(|| BotError::Protocol("no bot command given".to_string()))?;
+        let mut process = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (process.stdin.take(), process.stdout.take()) else {
+            return Err(BotError::Exited);
+        };
+
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let reply = line.map_err(BotError::from).and_then(|line| BotReply::decode(&line));
+                let failed = reply.is_err();
+                if sender.send(reply).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let mut bot = Self {
+            name: program.to_string(),
+            process,
+            stdin,
+            replies,
+            rules: rules.clone(),
+            fired: vec![vec![false; rules.width()]; rules.height()],
+            unreported: Vec::new(),
+            awaited: 0,
+            deadline: Instant::now(),
+            shots: VecDeque::new(),
+            failure: None,
+        };
+
+        bot.send(&BotCommand::Battleship(BOT_PROTOCOL_VERSION))?;
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            match bot.recv_until(deadline)? {
+                BotReply::Id(name) => bot.name = name,
+                BotReply::Ok(version) if version == BOT_PROTOCOL_VERSION => break,
+                BotReply::Ok(version) => return Err(BotError::VersionMismatch(version)),
+                reply => return Err(BotError::Protocol(format!("expected ok, got {:?}", reply))),
+            }
+        }
+        bot.send(&BotCommand::Rules(rules.clone()))?;
+        Ok(bot)
+    }
+
+    // What the bot calls itself, or its program until it says
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Asks the bot to lay out its fleet
+    pub fn place_fleet(&mut self) -> Result<Fleet, BotError> {
+        self.send(&BotCommand::Place)?;
+        let ships = match self.recv()? {
+            BotReply::Fleet(ships) => ships,
+            reply => return Err(BotError::Protocol(format!("expected fleet, got {:?}", reply))),
+        };
+
+        let mut fleet = Fleet::new(&self.rules);
+        for (ship, row, col, horizontal) in ships {
+            fleet
+                .place(ship, (row, col), Orientation::from_horizontal(horizontal))
+                .map_err(|error| BotError::InvalidFleet(format!("{:?} at {}: {}", ship, cell_name(row, col), error)))?;
+        }
+        if !fleet.is_complete() {
+            return Err(BotError::InvalidFleet(format!("{:?} not placed", fleet.unplaced())));
+        }
+        Ok(fleet)
+    }
+
+    fn send(&mut self, command: &BotCommand) -> Result<(), BotError> {
+        writeln!(self.stdin, "{}", command.encode())?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    // The next reply, skipping what the bot only says for its own sake
+    fn recv(&mut self) -> Result<BotReply, BotError> {
+        self.recv_until(Instant::now() + REPLY_TIMEOUT)
+    }
+
+    // Like `recv`, giving up at `deadline` however much the bot says in the meantime
+    fn recv_until(&mut self, deadline: Instant) -> Result<BotReply, BotError> {
+        loop {
+            match self.replies.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Ok(BotReply::Info(_))) => continue,
+                Ok(reply) => return reply,
+                Err(RecvTimeoutError::Timeout) => return Err(BotError::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(BotError::Exited),
+            }
+        }
+    }
+
+    // Tells the bot what its earlier shots hit, then asks it for more shots until
+    // `count` are asked for or in
+    fn ask(&mut self, history: &ShotHistory, count: usize) -> Result<(), BotError> {
+        while let Some(&(row, col)) = self.unreported.first() {
+            let Some(result) = history.result_at(row, col) else {
+                break;
+            };
+            self.unreported.remove(0);
+            self.send(&BotCommand::Result((row, col), result))?;
+        }
+
+        while self.awaited + self.shots.len() < count {
+            self.send(&BotCommand::Go)?;
+            if self.awaited == 0 {
+                self.deadline = Instant::now() + REPLY_TIMEOUT;
+            }
+            self.awaited += 1;
+        }
+        Ok(())
+    }
+
+    // Takes in the bot's answer to a go if one has come in, without waiting for it
+    fn try_read_shot(&mut self) -> Result<bool, BotError> {
+        loop {
+            match self.replies.try_recv() {
+                Ok(Ok(BotReply::Info(_))) => continue,
+                Ok(reply) => return self.take_shot(reply?).map(|()| true),
+                Err(TryRecvError::Empty) if Instant::now() < self.deadline => return Ok(false),
+                Err(TryRecvError::Empty) => return Err(BotError::Timeout),
+                Err(TryRecvError::Disconnected) => return Err(BotError::Exited),
+            }
+        }
+    }
+
+    fn take_shot(&mut self, reply: BotReply) -> Result<(), BotError> {
+        match reply {
+            BotReply::Fire(cell) => {
+                self.awaited -= 1;
+                self.deadline = Instant::now() + REPLY_TIMEOUT;
+                self.shots.push_back(cell);
+                Ok(())
+            }
+            reply => Err(BotError::Protocol(format!("expected fire, got {:?}", reply))),
+        }
+    }
+
+    // Asks for shots and takes in the answers that have come in so far
+    fn poll_shots(&mut self, history: &ShotHistory, count: usize) -> Result<(), BotError> {
+        self.ask(history, count)?;
+        while self.shots.len() < count && self.try_read_shot()? {}
+        Ok(())
+    }
+}
+
+// An external bot being started and laying out its fleet on a thread of its own, so a
+// slow bot does not hold up the game loop
+pub struct PendingBot {
+    result: Receiver<Result<(ExternalBot, Fleet), BotError>>,
+}
+
+impl PendingBot {
+    pub fn launch(command: &str, rules: &RuleSet) -> Self {
+        let (command, rules) = (command.to_string(), rules.clone());
+        let (sender, result) = mpsc::channel();
+        thread::spawn(move || {
+            let launched = ExternalBot::launch(&command, &rules).and_then(|mut bot| {
+                let fleet = bot.place_fleet()?;
+                Ok((bot, fleet))
+            });
+            let _ = sender.send(launched);
+        });
+        Self { result }
+    }
+
+    // The bot and its fleet once it is ready
+    pub fn poll(&self) -> Option<Result<(ExternalBot, Fleet), BotError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(BotError::Exited)),
+        }
+    }
+}
+
+impl ShotStrategy for ExternalBot {
+    fn choose_target(&mut self, history: &ShotHistory, _rng: &mut dyn RngCore) -> Result<(usize, usize), BotError> {
+        if let Some(error) = self.failure.take() {
+            return Err(error);
+        }
+        // Waits no longer than the bot has left to answer the go it was sent first
+        if self.shots.is_empty() {
+            self.ask(history, 1)?;
+            let reply = self.recv_until(self.deadline)?;
+            self.take_shot(reply)?;
+        }
+
+        let (row, col) = self.shots.pop_front().ok_or(BotError::Exited)?;
+        if row >= history.height() || col >= history.width() || self.fired[row][col] {
+            return Err(BotError::InvalidShot(row, col));
+        }
+        self.fired[row][col] = true;
+        self.unreported.push((row, col));
+        Ok((row, col))
+    }
+
+    fn is_ready(&mut self, history: &ShotHistory, count: usize) -> bool {
+        if self.failure.is_none() {
+            self.failure = self.poll_shots(history, count).err();
+        }
+        self.failure.is_some() || self.shots.len() >= count
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.send(&BotCommand::Quit);
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            if !matches!(self.process.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
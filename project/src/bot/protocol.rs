@@ -0,0 +1,84 @@
+use crate::board::ShotResult;
+use crate::net::encode_rules;
+use crate::rules::RuleSet;
+use crate::save::{format_result, parse_cell, parse_placement, parse_value, ShipPlacement};
+
+use super::BotError;
+
+// Bump whenever a command or reply changes. The bot says which version it speaks.
+pub const BOT_PROTOCOL_VERSION: u32 = 1;
+
+// What the game tells a bot on its standard input, one command per line. The bot
+// answers `battleship` with `id` and `ok`, `place` with `fleet` and `go` with `fire`.
+// Rows and columns count from 0.
+//
+//   battleship 1
+//   rules 10 10 Classic false Carrier,Battleship,Cruiser,Submarine,Destroyer Classic
+//   place
+//   go
+//   result 3,4 Hit:Carrier
+//   quit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotCommand {
+    // Sent first, with the protocol version the game speaks
+    Battleship(u32),
+    Rules(RuleSet),
+    // Asks for the bot's fleet layout
+    Place,
+    // Asks for the bot's next shot. In salvo games it is sent once for every shot of
+    // the volley, and the results only follow once the whole volley is fired.
+    Go,
+    // What one of the bot's shots hit
+    Result((usize, usize), ShotResult),
+    Quit,
+}
+
+impl BotCommand {
+    pub fn encode(&self) -> String {
+        match self {
+            BotCommand::Battleship(version) => format!("battleship {}", version),
+            BotCommand::Rules(rules) => format!("rules {}", encode_rules(rules)),
+            BotCommand::Place => "place".to_string(),
+            BotCommand::Go => "go".to_string(),
+            BotCommand::Result((row, col), result) => format!("result {},{} {}", row, col, format_result(*result)),
+            BotCommand::Quit => "quit".to_string(),
+        }
+    }
+}
+
+// What a bot writes on its standard output, one reply per line:
+//
+//   id name Hunter
+//   ok 1
+//   fleet Carrier,0,0,horizontal Battleship,2,0,vertical ...
+//   fire 3,4
+//   info anything the bot wants to say, which the game ignores
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BotReply {
+    // The bot's name, e.g. "id name Hunter"
+    Id(String),
+    // The bot is ready, and speaks this protocol version
+    Ok(u32),
+    Fleet(Vec<ShipPlacement>),
+    Fire((usize, usize)),
+    Info(String),
+}
+
+impl BotReply {
+    pub fn decode(line: &str) -> Result<Self, BotError> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let reply = match command {
+            "id" => match rest.split_once(' ') {
+                Some(("name", name)) => BotReply::Id(name.trim().to_string()),
+                _ => return Err(BotError::Protocol(format!("unknown id {:?}", rest))),
+            },
+            "ok" => BotReply::Ok(parse_value(rest)?),
+            "fleet" => BotReply::Fleet(rest.split_whitespace().map(parse_placement).collect::<Result<_, _>>()?),
+            "fire" => BotReply::Fire(parse_cell(rest)?),
+            "info" => BotReply::Info(rest.to_string()),
+            _ => return Err(BotError::Protocol(format!("unknown reply {:?}", line))),
+        };
+        Ok(reply)
+    }
+}
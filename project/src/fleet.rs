@@ -0,0 +1,226 @@
+use rand::Rng;
+
+use crate::board::{Board, LayoutStyle, PlacementError};
+use crate::rules::RuleSet;
+use crate::ship::{Orientation, PlacedShip, ShipId, ShipType};
+
+// Random layouts are drawn whole and thrown away if any ships clash, which makes every
+// legal layout equally likely. Crowded boards where that keeps failing fall back to
+// placing one ship at a time.
+const MAX_UNIFORM_ATTEMPTS: usize = 10_000;
+// Placing one ship at a time gives up on a layout after this many failed tries for one
+// ship and starts over, and gives up entirely after this many layouts
+const MAX_SHIP_ATTEMPTS: usize = 1000;
+const MAX_LAYOUT_ATTEMPTS: usize = 100;
+// Random layouts compared when looking for a spread out one
+const SPREAD_OUT_CANDIDATES: usize = 200;
+
+// One side's ships and the board they are placed on. Only the ships the rules hand
+// out can be placed, and each only as many times as the fleet holds it.
+#[derive(Clone, Debug)]
+pub struct Fleet {
+    board: Board,
+    // Ships of the rules' fleet that are not on the board, in the order they are listed
+    unplaced: Vec<ShipType>,
+}
+
+impl Fleet {
+    // The fleet `rules` ask for, none of it placed yet
+    pub fn new(rules: &RuleSet) -> Self {
+        Self {
+            board: Board::new(rules.width(), rules.height()).with_no_touching(rules.no_touching()),
+            unplaced: rules.fleet().to_vec(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub(crate) fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    pub fn unplaced(&self) -> &[ShipType] {
+        &self.unplaced
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unplaced.is_empty()
+    }
+
+    // Puts `ship` on the board with its bow at `origin`. Nothing changes if it does not
+    // fit there or the fleet has no more of it to place.
+    pub fn place(
+        &mut self,
+        ship: ShipType,
+        origin: (usize, usize),
+        orientation: Orientation,
+    ) -> Result<ShipId, PlacementError> {
+        let index = self.unplaced.iter().position(|&unplaced| unplaced == ship).ok_or(PlacementError::AlreadyPlaced)?;
+        let id = self.board.place(ship, origin, orientation)?;
+        self.unplaced.remove(index);
+        Ok(id)
+    }
+
+    // Takes the ship covering (row, col) off the board so it can be placed again. Only
+    // possible before any shots were fired at the board.
+    pub fn remove(&mut self, row: usize, col: usize) -> Option<PlacedShip> {
+        let ship = self.board.remove_ship(row, col)?;
+        self.unplaced.push(ship.ship_type());
+        Some(ship)
+    }
+
+    // Places every ship not placed yet, laid out in `style`. False and nothing changes if
+    // they could not be fitted on the board.
+    pub fn place_remaining<R: Rng>(&mut self, style: LayoutStyle, rng: &mut R) -> bool {
+        match style {
+            LayoutStyle::Random => self.place_randomly(rng),
+            LayoutStyle::SpreadOut => self.place_spread_out(rng),
+        }
+    }
+
+    // Every legal layout of the unplaced ships as likely as any other
+    fn place_randomly<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let longest = self.board.width().max(self.board.height());
+        if self.unplaced.iter().any(|ship| ship.size() > longest) {
+            return false;
+        }
+        let ships = self.unplaced.clone();
+
+        for _ in 0..MAX_UNIFORM_ATTEMPTS {
+            let placed = ships.iter().all(|&ship| {
+                let (origin, orientation) = self.board.random_position(ship, rng);
+                self.place(ship, origin, orientation).is_ok()
+            });
+            if placed {
+                return true;
+            }
+            self.unplace(&ships);
+        }
+
+        self.place_one_by_one(rng)
+    }
+
+    // The unplaced ships at random legal positions one after the other. Much quicker than
+    // `place_randomly` on crowded boards, but layouts that leave a lot of room for the
+    // last ships come up more often.
+    fn place_one_by_one<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let ships = self.unplaced.clone();
+
+        for _ in 0..MAX_LAYOUT_ATTEMPTS {
+            if ships.iter().all(|&ship| self.place_ship_randomly(ship, rng)) {
+                return true;
+            }
+            self.unplace(&ships);
+        }
+
+        false
+    }
+
+    fn place_ship_randomly<R: Rng>(&mut self, ship: ShipType, rng: &mut R) -> bool {
+        for _ in 0..MAX_SHIP_ATTEMPTS {
+            let origin = (rng.gen_range(0..self.board.height()), rng.gen_range(0..self.board.width()));
+            let orientation = Orientation::from_horizontal(rng.gen_bool(0.5));
+
+            if self.place(ship, origin, orientation).is_ok() {
+                return true;
+            }
+        }
+        false
+    }
+
+    // The random layout that is least likely to be found early, out of many tried
+    fn place_spread_out<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let coverage = self.board.coverage(&self.unplaced);
+        let mut best: Option<(u32, Fleet)> = None;
+
+        for _ in 0..SPREAD_OUT_CANDIDATES {
+            // Only the best candidate is kept, so they need not be drawn evenly
+            let mut candidate = self.clone();
+            if !candidate.place_one_by_one(rng) {
+                return false;
+            }
+            let score = candidate.board.spread_score(&coverage);
+            if best.as_ref().is_none_or(|(best_score, _)| score < *best_score) {
+                best = Some((score, candidate));
+            }
+        }
+
+        if let Some((_, fleet)) = best {
+            *self = fleet;
+        }
+        true
+    }
+
+    // Takes back a half finished layout, leaving `ships` unplaced as they were before it
+    fn unplace(&mut self, ships: &[ShipType]) {
+        let placed = ships.len() - self.unplaced.len();
+        self.board.truncate_ships(self.board.ships().len() - placed);
+        self.unplaced = ships.to_vec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn place_only_hands_out_the_rules_ships() {
+        let rules = RuleSet::classic();
+        let mut fleet = Fleet::new(&rules);
+
+        assert!(fleet.place(ShipType::Destroyer, (0, 0), Orientation::Horizontal).is_ok());
+        assert_eq!(
+            fleet.place(ShipType::Destroyer, (5, 5), Orientation::Horizontal),
+            Err(PlacementError::AlreadyPlaced)
+        );
+        let torpedo_boat = fleet.place(ShipType::TorpedoBoat, (5, 5), Orientation::Horizontal);
+        assert_eq!(torpedo_boat, Err(PlacementError::AlreadyPlaced));
+        assert_eq!(fleet.place(ShipType::Carrier, (0, 8), Orientation::Horizontal), Err(PlacementError::OutOfBounds));
+        assert_eq!(fleet.unplaced().len(), rules.fleet().len() - 1);
+
+        assert!(fleet.remove(0, 1).is_some());
+        assert_eq!(fleet.unplaced().len(), rules.fleet().len());
+        assert!(fleet.board().ships().is_empty());
+    }
+
+    #[test]
+    fn place_remaining_fits_the_whole_fleet() {
+        let rules = RuleSet::classic().with_no_touching(true);
+        let mut rng = StdRng::seed_from_u64(7);
+        for style in LayoutStyle::ALL {
+            for _ in 0..10 {
+                let mut fleet = Fleet::new(&rules);
+                assert!(fleet.place_remaining(style, &mut rng));
+                assert!(fleet.is_complete());
+                assert_eq!(fleet.board().ships().len(), rules.fleet().len());
+            }
+        }
+
+        // More cells of ship than the board has
+        let crowded = RuleSet::new("Crowded", vec![ShipType::Carrier; 6]).with_board_size(5, 5);
+        let mut fleet = Fleet::new(&crowded);
+        assert!(!fleet.place_remaining(LayoutStyle::Random, &mut rng));
+        assert_eq!(fleet.unplaced(), crowded.fleet());
+        assert!(fleet.board().ships().is_empty());
+    }
+
+    #[test]
+    fn place_remaining_keeps_ships_already_placed() {
+        let rules = RuleSet::classic();
+        let mut fleet = Fleet::new(&rules);
+        fleet.place(ShipType::Carrier, (0, 0), Orientation::Horizontal).unwrap();
+
+        assert!(fleet.place_remaining(LayoutStyle::Random, &mut StdRng::seed_from_u64(3)));
+        assert_eq!(fleet.board().ships().len(), rules.fleet().len());
+        assert_eq!(fleet.board().ships()[0].ship_type(), ShipType::Carrier);
+        assert_eq!(fleet.board().ships()[0].cells()[0], (0, 0));
+    }
+}
@@ -0,0 +1,774 @@
+use std::fmt;
+
+use crate::ai::{Difficulty, RandomAi, ShotHistory, ShotStrategy};
+use crate::board::{Board, LayoutStyle, PlacementError, ShotResult};
+use crate::bot::BotError;
+use crate::fleet::Fleet;
+use crate::rng::MatchRng;
+use crate::rules::{GameMode, RuleSet};
+use crate::save::SaveData;
+use crate::ship::{Orientation, PlacedShip, ShipId, ShipType};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Player {
+    Human,
+    Computer,
+}
+
+// The shots one side fired in one turn and the answers they got
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Turn {
+    shooter: Player,
+    shots: Vec<(usize, usize, ShotResult)>,
+}
+
+impl Turn {
+    pub fn new(shooter: Player, shots: Vec<(usize, usize, ShotResult)>) -> Self {
+        Self { shooter, shots }
+    }
+
+    pub fn shooter(&self) -> Player {
+        self.shooter
+    }
+
+    pub fn shots(&self) -> &[(usize, usize, ShotResult)] {
+        &self.shots
+    }
+
+    pub fn results(&self) -> Vec<ShotResult> {
+        self.shots.iter().map(|&(_, _, result)| result).collect()
+    }
+}
+
+// What a single shot did
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShotOutcome {
+    Miss,
+    Hit(ShipType),
+    Sunk(ShipType),
+    // Sank the last ship the shooter needed to win the match
+    GameWon(ShipType),
+}
+
+impl ShotOutcome {
+    // The answer the target board gives, which is all the shooter learns
+    pub fn result(&self) -> ShotResult {
+        match *self {
+            ShotOutcome::Miss => ShotResult::Miss,
+            ShotOutcome::Hit(ship) => ShotResult::Hit(ship),
+            ShotOutcome::Sunk(ship) | ShotOutcome::GameWon(ship) => ShotResult::Sunk(ship),
+        }
+    }
+}
+
+// Why a shot or volley was not fired. Nothing changes in the match when one is returned.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShotError {
+    OutOfBounds,
+    AlreadyFired,
+    NotYourTurn,
+    MatchOver,
+    // A volley must hold exactly as many shots as the shooter has left this turn
+    WrongShotCount { expected: usize, got: usize },
+    // The player's shots at a LAN opponent are answered over the network instead
+    RemoteBoard,
+    // The player's turns are not played by a strategy
+    NoStrategy,
+    // The board fired at is still missing some of its fleet
+    FleetNotPlaced,
+    // The shooter's strategy failed to pick its volley, which loses them the match
+    Forfeited,
+}
+
+impl fmt::Display for ShotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShotError::OutOfBounds => write!(f, "the cell is off the board"),
+            ShotError::AlreadyFired => write!(f, "the cell was already fired at"),
+            ShotError::NotYourTurn => write!(f, "it is not the shooter's turn"),
+            ShotError::MatchOver => write!(f, "the match is over"),
+            ShotError::WrongShotCount { expected, got } => write!(f, "expected {} shots, got {}", expected, got),
+            ShotError::RemoteBoard => write!(f, "the opponent's board is only known to the opponent"),
+            ShotError::NoStrategy => write!(f, "the player's turns are not played by a strategy"),
+            ShotError::FleetNotPlaced => write!(f, "the fleet fired at is not placed yet"),
+            ShotError::Forfeited => write!(f, "the shooter's strategy failed and forfeited the match"),
+        }
+    }
+}
+
+impl std::error::Error for ShotError {}
+
+pub struct Game {
+    rules: RuleSet,
+    player_fleet: Fleet,
+    computer_fleet: Fleet,
+    is_player_turn: bool,
+    strategy: Box<dyn ShotStrategy>,
+    // None when the computer plays a custom strategy
+    difficulty: Option<Difficulty>,
+    // The opponent is another player over the network. Their ships are not in
+    // `computer_fleet`, whose board only holds the answers they gave.
+    remote: bool,
+    // What the computer has learned from its own shots
+    computer_history: ShotHistory,
+    // A strategy that plays the player's turns instead of them, e.g. in simulated
+    // matches, and what it has learned from its shots
+    autopilot: Option<(Box<dyn ShotStrategy>, ShotHistory)>,
+    total_player_hits: usize,
+    total_computer_hits: usize,
+    // Every turn played so far, in order
+    turns: Vec<Turn>,
+    // Shots of the turn being played, which ends once `volley_size` shots are fired
+    volley: Vec<(usize, usize, ShotResult)>,
+    volley_size: usize,
+    // The side whose strategy failed, e.g. an external bot that stopped answering, and
+    // why. They lose the match.
+    forfeit: Option<(Player, BotError)>,
+    rng: MatchRng,
+}
+
+impl Game {
+    pub fn new(rules: RuleSet, difficulty: Difficulty) -> Self {
+        let mut game = Self::with_strategy(rules, difficulty.strategy());
+        game.difficulty = Some(difficulty);
+        game
+    }
+
+    pub fn with_strategy(rules: RuleSet, strategy: Box<dyn ShotStrategy>) -> Self {
+        Self {
+            player_fleet: Fleet::new(&rules),
+            computer_fleet: Fleet::new(&rules),
+            computer_history: ShotHistory::new(&rules),
+            autopilot: None,
+            rules,
+            is_player_turn: true,
+            strategy,
+            difficulty: None,
+            remote: false,
+            total_player_hits: 0,
+            total_computer_hits: 0,
+            turns: Vec::new(),
+            volley: Vec::new(),
+            volley_size: 0,
+            forfeit: None,
+            rng: MatchRng::from_entropy(),
+        }
+    }
+
+    // Draws every random number of the match from `seed` instead of a random one, so the
+    // same seed and the same moves by the player always bring the same computer fleet
+    // and shots
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = MatchRng::new(seed);
+        self
+    }
+
+    // Has `strategy` play the player's turns through `player_turn`, under the same rules
+    // as the computer's turns
+    pub fn with_player_strategy(mut self, strategy: Box<dyn ShotStrategy>) -> Self {
+        self.autopilot = Some((strategy, ShotHistory::new(&self.rules)));
+        self
+    }
+
+    // A match against another player over the network. The computer's board starts
+    // without ships and the opponent answers the player's shots.
+    pub fn networked(rules: RuleSet, player_goes_first: bool) -> Self {
+        let mut game = Self::with_strategy(rules, Box::new(RandomAi));
+        game.remote = true;
+        game.is_player_turn = player_goes_first;
+        game
+    }
+
+    // Rebuilds a saved match by placing both fleets and replaying every shot turn by turn,
+    // which also brings back the turn log and the computer's memory of its own shots.
+    // None if the placements or shots are not possible under the saved rules.
+    pub(crate) fn restore(save: SaveData) -> Option<Self> {
+        let mut game = match save.difficulty {
+            Some(difficulty) => Self::new(save.rules, difficulty),
+            // Matches without a difficulty are only ever watched, never played on
+            None => Self::with_strategy(save.rules, Box::new(RandomAi)),
+        };
+        for &(ship, row, col, horizontal) in &save.player_ships {
+            game.player_fleet.place(ship, (row, col), Orientation::from_horizontal(horizontal)).ok()?;
+        }
+        for &(ship, row, col, horizontal) in &save.computer_ships {
+            game.computer_fleet.place(ship, (row, col), Orientation::from_horizontal(horizontal)).ok()?;
+        }
+
+        // Each turn takes as many shots from the shooter's list as it was allowed to fire
+        let mut player_shots = save.player_shots.iter().copied();
+        let mut computer_shots = save.computer_shots.iter().copied();
+        loop {
+            let (shooter, shots) = if game.is_player_turn {
+                (Player::Human, &mut player_shots)
+            } else {
+                (Player::Computer, &mut computer_shots)
+            };
+            let targets: Vec<_> = shots.by_ref().take(game.shots_per_turn(shooter)).collect();
+            if targets.is_empty() {
+                break;
+            }
+            game.fire_salvo(shooter, &targets).ok()?;
+        }
+        if player_shots.next().is_some() || computer_shots.next().is_some() || game.is_player_turn != save.is_player_turn {
+            return None;
+        }
+
+        game.rng = save.rng;
+        Some(game)
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn player_board(&self) -> &Board {
+        self.player_fleet.board()
+    }
+
+    pub fn computer_board(&self) -> &Board {
+        self.computer_fleet.board()
+    }
+
+    pub fn player_fleet(&self) -> &Fleet {
+        &self.player_fleet
+    }
+
+    pub fn is_player_turn(&self) -> bool {
+        self.is_player_turn
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+
+    pub fn rng(&self) -> &MatchRng {
+        &self.rng
+    }
+
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    pub fn place_player_ship(
+        &mut self,
+        ship: ShipType,
+        origin: (usize, usize),
+        orientation: Orientation,
+    ) -> Result<ShipId, PlacementError> {
+        self.player_fleet.place(ship, origin, orientation)
+    }
+
+    // Picks a placed ship back up before the match starts
+    pub fn remove_player_ship(&mut self, row: usize, col: usize) -> Option<PlacedShip> {
+        self.player_fleet.remove(row, col)
+    }
+
+    // Takes every ship off the player's board before the match starts
+    pub fn clear_player_ships(&mut self) {
+        self.player_fleet = Fleet::new(&self.rules);
+    }
+
+    // Replaces the player's ships with the whole fleet laid out automatically. False
+    // and nothing changes if the fleet could not be fitted on the board.
+    pub fn place_player_ships(&mut self, style: LayoutStyle) -> bool {
+        let mut fleet = Fleet::new(&self.rules);
+        if !fleet.place_remaining(style, &mut self.rng) {
+            return false;
+        }
+        self.player_fleet = fleet;
+        true
+    }
+
+    // False if the fleet could not be fitted on the board
+    pub fn place_computer_ships(&mut self, style: LayoutStyle) -> bool {
+        self.computer_fleet.place_remaining(style, &mut self.rng)
+    }
+
+    // Replaces the player's ships with a fleet laid out elsewhere, e.g. by an external
+    // bot. False and nothing changes if it is not the whole fleet the rules ask for.
+    pub fn set_player_fleet(&mut self, fleet: Fleet) -> bool {
+        if !self.fits_rules(&fleet) {
+            return false;
+        }
+        self.player_fleet = fleet;
+        true
+    }
+
+    pub fn set_computer_fleet(&mut self, fleet: Fleet) -> bool {
+        if !self.fits_rules(&fleet) {
+            return false;
+        }
+        self.computer_fleet = fleet;
+        true
+    }
+
+    // Hands the computer's seat to `strategy` with the fleet it laid out itself, e.g. an
+    // external bot that was started after the match was set up. False and nothing
+    // changes if the fleet breaks the rules or the computer has already been fired at.
+    pub fn set_computer_player(&mut self, strategy: Box<dyn ShotStrategy>, fleet: Fleet) -> bool {
+        if !self.turns.is_empty() || !self.volley.is_empty() || !self.set_computer_fleet(fleet) {
+            return false;
+        }
+        self.strategy = strategy;
+        self.difficulty = None;
+        true
+    }
+
+    fn fits_rules(&self, fleet: &Fleet) -> bool {
+        let board = fleet.board();
+        let order = |ship: &ShipType| ShipType::ALL.iter().position(|other| other == ship);
+        let mut ships: Vec<_> = board.ships().iter().map(|ship| ship.ship_type()).collect();
+        let mut expected = self.rules.fleet().to_vec();
+        ships.sort_by_key(order);
+        expected.sort_by_key(order);
+        fleet.is_complete()
+            && board.width() == self.rules.width()
+            && board.height() == self.rules.height()
+            && ships == expected
+    }
+
+    // How many shots `player` gets this turn: one in classic games, one per ship still
+    // afloat in salvo games, but never more than there are cells left to fire at. A
+    // strategy also skips cells it already knows to be water from the no-touching rule.
+    pub fn shots_per_turn(&self, player: Player) -> usize {
+        let target_board = match player {
+            Player::Human => self.computer_board(),
+            Player::Computer => self.player_board(),
+        };
+
+        let shots = match self.rules.mode() {
+            GameMode::Classic => 1,
+            GameMode::Salvo => self.rules.fleet().len() - self.sunk_ships(player).len(),
+        };
+        let shots = shots.min(target_board.unfired_cells());
+        match (player, &self.autopilot) {
+            (Player::Computer, _) if !self.remote => shots.min(self.computer_history.unresolved_cells()),
+            (Player::Human, Some((_, history))) => shots.min(history.unresolved_cells()),
+            _ => shots,
+        }
+    }
+
+    // Ships of `player` that were reported sunk, in the order they went down
+    pub fn sunk_ships(&self, player: Player) -> Vec<ShipType> {
+        self.turns
+            .iter()
+            .filter(|turn| turn.shooter() != player)
+            .flat_map(|turn| turn.shots())
+            .filter_map(|&(_, _, result)| match result {
+                ShotResult::Sunk(ship) => Some(ship),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn turns_taken(&self, player: Player) -> usize {
+        self.turns.iter().filter(|turn| turn.shooter() == player).count()
+    }
+
+    // Shots `player` has fired over the whole match
+    pub fn shots_fired(&self, player: Player) -> usize {
+        self.turns
+            .iter()
+            .filter(|turn| turn.shooter() == player)
+            .map(|turn| turn.shots().len())
+            .sum()
+    }
+
+    pub fn hits(&self, player: Player) -> usize {
+        match player {
+            Player::Human => self.total_player_hits,
+            Player::Computer => self.total_computer_hits,
+        }
+    }
+
+    // Shots `player` still has to fire this turn, none if it is not their turn
+    pub fn shots_left(&self, player: Player) -> usize {
+        if self.shooter() != player {
+            0
+        } else if self.volley.is_empty() {
+            self.shots_per_turn(player)
+        } else {
+            self.volley_size - self.volley.len()
+        }
+    }
+
+    fn shooter(&self) -> Player {
+        if self.is_player_turn {
+            Player::Human
+        } else {
+            Player::Computer
+        }
+    }
+
+    // Fires one shot of `player`'s turn. The turn passes once they have fired all the
+    // shots they get, so in a classic game every shot ends the turn. The shots of a
+    // volley land together, so a volley that wins the match is still fired in full.
+    pub fn fire(&mut self, player: Player, (row, col): (usize, usize)) -> Result<ShotOutcome, ShotError> {
+        if player == Player::Human && self.remote {
+            return Err(ShotError::RemoteBoard);
+        }
+        self.check_shot(player, (row, col))?;
+
+        if self.volley.is_empty() {
+            self.volley_size = self.shots_per_turn(player);
+        }
+        let was_won = self.winner().is_some();
+        let result = match player {
+            Player::Human => self.computer_fleet.board_mut().fire(row, col),
+            Player::Computer => self.player_fleet.board_mut().fire(row, col),
+        }
+        .ok_or(ShotError::AlreadyFired)?;
+
+        if result.is_hit() {
+            match player {
+                Player::Human => self.total_player_hits += 1,
+                Player::Computer => self.total_computer_hits += 1,
+            }
+        }
+        match (player, &mut self.autopilot) {
+            (Player::Computer, _) => self.computer_history.record(row, col, result),
+            (Player::Human, Some((_, history))) => history.record(row, col, result),
+            (Player::Human, None) => {}
+        }
+        self.volley.push((row, col, result));
+
+        if self.volley.len() == self.volley_size {
+            let shots = std::mem::take(&mut self.volley);
+            self.end_turn(Turn::new(player, shots));
+        }
+
+        Ok(match result {
+            ShotResult::Sunk(ship) if !was_won && self.winner().is_some() => ShotOutcome::GameWon(ship),
+            ShotResult::Miss => ShotOutcome::Miss,
+            ShotResult::Hit(ship) => ShotOutcome::Hit(ship),
+            ShotResult::Sunk(ship) => ShotOutcome::Sunk(ship),
+        })
+    }
+
+    // Fires `player`'s whole volley, which must hold exactly the shots they have left
+    // this turn at distinct cells. Nothing is fired if any of them could not be.
+    pub fn fire_salvo(&mut self, player: Player, targets: &[(usize, usize)]) -> Result<Vec<ShotOutcome>, ShotError> {
+        if player == Player::Human && self.remote {
+            return Err(ShotError::RemoteBoard);
+        }
+        self.check_salvo(player, targets)?;
+        targets.iter().map(|&target| self.fire(player, target)).collect()
+    }
+
+    // In a LAN match the computer's board holds the opponent's answers, which is enough
+    // to check the player's shots before they are sent
+    fn check_shot(&self, player: Player, (row, col): (usize, usize)) -> Result<(), ShotError> {
+        let (fleet, remote) = match player {
+            Player::Human => (&self.computer_fleet, self.remote),
+            Player::Computer => (&self.player_fleet, false),
+        };
+        let board = fleet.board();
+
+        if self.winner().is_some() && self.volley.is_empty() {
+            Err(ShotError::MatchOver)
+        } else if self.shooter() != player {
+            Err(ShotError::NotYourTurn)
+        } else if !fleet.is_complete() && !remote {
+            Err(ShotError::FleetNotPlaced)
+        } else if !board.in_bounds(row, col) {
+            Err(ShotError::OutOfBounds)
+        } else if !board.can_fire(row, col) {
+            Err(ShotError::AlreadyFired)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_salvo(&self, player: Player, targets: &[(usize, usize)]) -> Result<(), ShotError> {
+        for (i, &target) in targets.iter().enumerate() {
+            self.check_shot(player, target)?;
+            if targets[..i].contains(&target) {
+                return Err(ShotError::AlreadyFired);
+            }
+        }
+
+        let expected = self.shots_left(player);
+        if targets.len() != expected {
+            return Err(ShotError::WrongShotCount { expected, got: targets.len() });
+        }
+        Ok(())
+    }
+
+    // Whether `player` may fire `targets` as their volley right now. The player's
+    // volleys in a LAN match are checked the same way before they are sent.
+    pub fn is_valid_salvo(&self, player: Player, targets: &[(usize, usize)]) -> bool {
+        self.check_salvo(player, targets).is_ok()
+    }
+
+    // Networked matches: records the opponent's answers to the player's volley. Returns
+    // false and records nothing if the answers do not fit the volley, or claim more hits
+    // or sunk ships than the opponent's fleet has.
+    pub fn record_player_results(&mut self, targets: &[(usize, usize)], results: &[ShotResult]) -> bool {
+        if !self.remote || !self.is_valid_salvo(Player::Human, targets) || targets.len() != results.len() {
+            return false;
+        }
+        let hits = results.iter().filter(|result| result.is_hit()).count();
+        if self.total_player_hits + hits > self.rules.win_condition() {
+            return false;
+        }
+        let mut sunk = self.sunk_ships(Player::Computer);
+        sunk.extend(results.iter().filter_map(|&result| match result {
+            ShotResult::Sunk(ship) => Some(ship),
+            _ => None,
+        }));
+        let fleet = self.rules.fleet();
+        let count = |ships: &[ShipType], ship: ShipType| ships.iter().filter(|&&other| other == ship).count();
+        if sunk.iter().any(|&ship| count(&sunk, ship) > count(fleet, ship)) {
+            return false;
+        }
+
+        let mut shots = Vec::with_capacity(targets.len());
+        for (&(row, col), &result) in targets.iter().zip(results) {
+            self.computer_fleet.board_mut().record_result(row, col, result);
+            if result.is_hit() {
+                self.total_player_hits += 1;
+            }
+            shots.push((row, col, result));
+        }
+        self.end_turn(Turn::new(Player::Human, shots));
+        true
+    }
+
+    // Fires the computer's volley at the player's board. The strategy picks every target
+    // before any of the results are revealed to it.
+    pub fn computer_turn(&mut self) -> Result<Vec<ShotOutcome>, ShotError> {
+        if self.remote {
+            return Err(ShotError::RemoteBoard);
+        }
+        self.check_turn(Player::Computer)?;
+
+        let count = self.shots_left(Player::Computer);
+        match choose_targets(self.strategy.as_mut(), &mut self.computer_history, &mut self.rng, count) {
+            Ok(targets) => self.fire_salvo(Player::Computer, &targets),
+            Err(error) => Err(self.forfeit_match(Player::Computer, error)),
+        }
+    }
+
+    // Whether `computer_turn` can fire right away rather than wait for the strategy,
+    // e.g. for an external bot to answer
+    pub fn is_computer_ready(&mut self) -> bool {
+        let count = self.shots_left(Player::Computer);
+        self.strategy.is_ready(&self.computer_history, count)
+    }
+
+    // Plays the player's turn with the strategy given to `with_player_strategy`
+    pub fn player_turn(&mut self) -> Result<Vec<ShotOutcome>, ShotError> {
+        self.check_turn(Player::Human)?;
+
+        let count = self.shots_left(Player::Human);
+        let (strategy, history) = self.autopilot.as_mut().ok_or(ShotError::NoStrategy)?;
+        match choose_targets(strategy.as_mut(), history, &mut self.rng, count) {
+            Ok(targets) => self.fire_salvo(Player::Human, &targets),
+            Err(error) => Err(self.forfeit_match(Player::Human, error)),
+        }
+    }
+
+    fn forfeit_match(&mut self, player: Player, error: BotError) -> ShotError {
+        self.forfeit = Some((player, error));
+        ShotError::Forfeited
+    }
+
+    // The side that lost by forfeit, and why
+    pub fn forfeit(&self) -> Option<(Player, &BotError)> {
+        self.forfeit.as_ref().map(|(player, error)| (*player, error))
+    }
+
+    fn check_turn(&self, player: Player) -> Result<(), ShotError> {
+        if self.winner().is_some() {
+            Err(ShotError::MatchOver)
+        } else if self.shooter() != player {
+            Err(ShotError::NotYourTurn)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn end_turn(&mut self, turn: Turn) {
+        self.turns.push(turn);
+        self.is_player_turn = !self.is_player_turn;
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        let win_condition = self.rules.win_condition();
+        if let Some((loser, _)) = self.forfeit {
+            Some(if loser == Player::Human { Player::Computer } else { Player::Human })
+        } else if self.total_player_hits >= win_condition {
+            Some(Player::Human)
+        } else if self.total_computer_hits >= win_condition {
+            Some(Player::Computer)
+        } else {
+            None
+        }
+    }
+}
+
+// Has `strategy` pick `count` cells to fire at together. Each pick is kept from being
+// picked again, as the results only come in once the whole volley is fired.
+fn choose_targets(
+    strategy: &mut dyn ShotStrategy,
+    history: &mut ShotHistory,
+    rng: &mut MatchRng,
+    count: usize,
+) -> Result<Vec<(usize, usize)>, BotError> {
+    let mut targets = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (row, col) = strategy.choose_target(history, rng)?;
+        history.mark_pending(row, col);
+        targets.push((row, col));
+    }
+    Ok(targets)
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(RuleSet::default(), Difficulty::Medium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A LAN match with one destroyer a side, the player's placed in the top left corner
+    fn tiny_networked_match() -> Game {
+        let mut game = Game::networked(RuleSet::new("Tiny", vec![ShipType::Destroyer]), true);
+        game.place_player_ship(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+        game
+    }
+
+    // A classic match with one destroyer a side, each in the top left corner
+    fn tiny_match() -> Game {
+        let rules = RuleSet::new("Tiny", vec![ShipType::Destroyer]);
+        let mut game = Game::new(rules.clone(), Difficulty::Easy).with_seed(1);
+        let mut fleet = Fleet::new(&rules);
+        fleet.place(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+        assert!(game.set_computer_fleet(fleet));
+        game.place_player_ship(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+        game
+    }
+
+    #[test]
+    fn fire_rejects_illegal_shots_without_changing_anything() {
+        let mut game = tiny_match();
+
+        assert_eq!(game.fire(Player::Human, (10, 0)), Err(ShotError::OutOfBounds));
+        assert_eq!(game.fire(Player::Human, (0, usize::MAX)), Err(ShotError::OutOfBounds));
+        assert_eq!(game.fire(Player::Computer, (5, 5)), Err(ShotError::NotYourTurn));
+        assert!(game.turns().is_empty() && game.is_player_turn());
+
+        assert_eq!(game.fire(Player::Human, (5, 5)), Ok(ShotOutcome::Miss));
+        game.fire(Player::Computer, (5, 5)).unwrap();
+        assert_eq!(game.fire(Player::Human, (5, 5)), Err(ShotError::AlreadyFired));
+        assert_eq!(game.turns().len(), 2);
+        assert!(game.is_player_turn());
+    }
+
+    #[test]
+    fn fire_needs_the_fleet_fired_at_placed() {
+        let rules = RuleSet::new("Tiny", vec![ShipType::Destroyer]);
+        let mut game = Game::new(rules, Difficulty::Easy).with_seed(1);
+
+        assert_eq!(game.fire(Player::Human, (5, 5)), Err(ShotError::FleetNotPlaced));
+        assert!(game.place_computer_ships(LayoutStyle::Random));
+        game.fire(Player::Human, (5, 5)).unwrap();
+        assert_eq!(game.computer_turn(), Err(ShotError::FleetNotPlaced));
+        assert!(game.turns().len() == 1 && !game.is_player_turn());
+    }
+
+    #[test]
+    fn fire_reports_the_winning_shot() {
+        let mut game = tiny_match();
+
+        assert_eq!(game.fire(Player::Human, (0, 0)), Ok(ShotOutcome::Hit(ShipType::Destroyer)));
+        game.fire(Player::Computer, (5, 5)).unwrap();
+        assert_eq!(game.fire(Player::Human, (0, 1)), Ok(ShotOutcome::GameWon(ShipType::Destroyer)));
+        assert_eq!(game.winner(), Some(Player::Human));
+        assert_eq!(game.computer_turn(), Err(ShotError::MatchOver));
+        assert_eq!(game.fire(Player::Computer, (6, 6)), Err(ShotError::MatchOver));
+    }
+
+    #[test]
+    fn salvo_must_be_the_whole_volley() {
+        let rules = RuleSet::classic().with_mode(GameMode::Salvo);
+        let mut game = Game::new(rules, Difficulty::Easy).with_seed(1);
+        assert!(game.place_player_ships(LayoutStyle::Random) && game.place_computer_ships(LayoutStyle::Random));
+
+        let got = game.fire_salvo(Player::Human, &[(0, 0), (0, 1)]);
+        assert_eq!(got, Err(ShotError::WrongShotCount { expected: 5, got: 2 }));
+        let repeated = game.fire_salvo(Player::Human, &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 0)]);
+        assert_eq!(repeated, Err(ShotError::AlreadyFired));
+        assert!(game.turns().is_empty());
+
+        assert_eq!(game.fire_salvo(Player::Human, &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]).unwrap().len(), 5);
+        assert_eq!(game.computer_turn().unwrap().len(), 5);
+    }
+
+    // Stands in for an external bot that stopped answering
+    struct BrokenStrategy;
+
+    impl ShotStrategy for BrokenStrategy {
+        fn choose_target(&mut self, _: &ShotHistory, _: &mut dyn rand::RngCore) -> Result<(usize, usize), BotError> {
+            Err(BotError::Timeout)
+        }
+    }
+
+    #[test]
+    fn failing_strategy_forfeits_the_match() {
+        let rules = RuleSet::new("Tiny", vec![ShipType::Destroyer]);
+        let mut game = Game::with_strategy(rules, Box::new(BrokenStrategy)).with_seed(1);
+        assert!(game.place_player_ships(LayoutStyle::Random) && game.place_computer_ships(LayoutStyle::Random));
+        game.fire(Player::Human, (5, 5)).unwrap();
+
+        assert!(game.is_computer_ready());
+        assert_eq!(game.computer_turn(), Err(ShotError::Forfeited));
+        assert_eq!(game.winner(), Some(Player::Human));
+        assert!(matches!(game.forfeit(), Some((Player::Computer, BotError::Timeout))));
+        assert_eq!(game.computer_turn(), Err(ShotError::MatchOver));
+        assert_eq!(game.turns().len(), 1);
+    }
+
+    #[test]
+    fn computer_seat_is_only_handed_over_before_the_first_shot() {
+        let rules = RuleSet::new("Tiny", vec![ShipType::Destroyer]);
+        let mut fleet = Fleet::new(&rules);
+        let mut game = Game::new(rules.clone(), Difficulty::Easy).with_seed(1);
+
+        assert!(!game.set_computer_player(Box::new(BrokenStrategy), fleet.clone()));
+        fleet.place(ShipType::Destroyer, (3, 3), Orientation::Vertical).unwrap();
+        assert!(game.set_computer_player(Box::new(BrokenStrategy), fleet.clone()));
+        assert_eq!(game.difficulty(), None);
+        assert_eq!(game.computer_board().ships()[0].cells()[0], (3, 3));
+
+        game.place_player_ship(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+        game.fire(Player::Human, (5, 5)).unwrap();
+        assert!(!game.set_computer_player(Box::new(RandomAi), fleet));
+        assert_eq!(game.computer_turn(), Err(ShotError::Forfeited));
+    }
+
+    #[test]
+    fn remote_answers_cannot_claim_more_than_the_fleet() {
+        let mut game = tiny_networked_match();
+        let sunk = ShotResult::Sunk(ShipType::Destroyer);
+        assert!(!game.record_player_results(&[(0, 0)], &[ShotResult::Sunk(ShipType::Carrier)]));
+        assert!(game.record_player_results(&[(0, 0)], &[ShotResult::Hit(ShipType::Destroyer)]));
+        game.fire(Player::Computer, (5, 5)).unwrap();
+        assert!(game.record_player_results(&[(0, 1)], &[sunk]));
+        assert_eq!(game.winner(), Some(Player::Human));
+
+        // A second sinking of the only destroyer is one hit too many
+        let mut game = tiny_networked_match();
+        assert!(game.record_player_results(&[(0, 0)], &[sunk]));
+        game.fire(Player::Computer, (5, 5)).unwrap();
+        assert!(!game.record_player_results(&[(0, 1)], &[sunk]));
+    }
+}
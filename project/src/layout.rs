@@ -0,0 +1,293 @@
+use ggez::graphics::Rect;
+
+use battleship::RuleSet;
+
+// Every screen is laid out on a 1600x1000 design canvas, which is scaled to fit the
+// window and centered in it. Drawing and hit-testing both ask this module for their
+// rectangles, so what is drawn is always what can be clicked.
+const DESIGN_WIDTH: f32 = 1600.0;
+const DESIGN_HEIGHT: f32 = 1000.0;
+
+const BOARD_EXTENT: f32 = 400.0; // Design pixels taken by the longer side of a board
+const BOARD_SPACING: f32 = 100.0; // Gap between either board and the middle of the screen
+const MODEL_CELL_SIZE: f32 = 40.0; // Cell size of the ship models in the ship lists
+const MENU_Y: [f32; 4] = [160.0, 230.0, 300.0, 370.0]; // Start, Continue, Replays, Exit
+const MENU_WIDTH: f32 = 260.0;
+const LAN_OFFSET: f32 = 350.0; // Host and Join sit this far either side of Start
+const OPTIONS_Y: f32 = 500.0; // First row of match options on the start screen
+const OPTION_ROW_HEIGHT: f32 = 100.0;
+const LIST_Y: f32 = 150.0; // First entry of the replay list
+const LIST_STEP: f32 = 50.0;
+const BOTTOM_ROW_Y: f32 = 900.0; // Back buttons, replay controls and game over buttons
+const PLACEMENT_BOARD: (f32, f32) = (100.0, 100.0); // Top left corner of the board on the placement screen
+const PLACEMENT_BUTTONS_Y: f32 = 600.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MenuItem {
+    Start,
+    Continue,
+    Replays,
+    Exit,
+    Host,
+    Join,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShipList {
+    Player,
+    Opponent,
+    Placement,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Layout {
+    scale: f32,
+    // Where the design canvas starts in the window
+    origin: (f32, f32),
+}
+
+impl Layout {
+    pub fn new(window_width: f32, window_height: f32) -> Self {
+        let scale = (window_width / DESIGN_WIDTH).min(window_height / DESIGN_HEIGHT);
+        Self {
+            scale,
+            origin: ((window_width - DESIGN_WIDTH * scale) / 2.0, (window_height - DESIGN_HEIGHT * scale) / 2.0),
+        }
+    }
+
+    // Text size in pixels for a size given on the design canvas
+    pub fn font(&self, size: f32) -> f32 {
+        size * self.scale
+    }
+
+    // A rectangle on the design canvas in window pixels
+    fn rect(&self, x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect::new(
+            self.origin.0 + x * self.scale,
+            self.origin.1 + y * self.scale,
+            width * self.scale,
+            height * self.scale,
+        )
+    }
+
+    // A full width band, for centered headings
+    fn band(&self, y: f32, height: f32) -> Rect {
+        self.rect(0.0, y, DESIGN_WIDTH, height)
+    }
+
+    pub fn title(&self) -> Rect {
+        self.band(50.0, 70.0)
+    }
+
+    pub fn menu_item(&self, item: MenuItem) -> Rect {
+        let (center_x, y) = match item {
+            MenuItem::Start => (DESIGN_WIDTH / 2.0, MENU_Y[0]),
+            MenuItem::Continue => (DESIGN_WIDTH / 2.0, MENU_Y[1]),
+            MenuItem::Replays => (DESIGN_WIDTH / 2.0, MENU_Y[2]),
+            MenuItem::Exit => (DESIGN_WIDTH / 2.0, MENU_Y[3]),
+            MenuItem::Host => (DESIGN_WIDTH / 2.0 - LAN_OFFSET, MENU_Y[0]),
+            MenuItem::Join => (DESIGN_WIDTH / 2.0 + LAN_OFFSET, MENU_Y[0]),
+        };
+        self.rect(center_x - MENU_WIDTH / 2.0, y, MENU_WIDTH, 55.0)
+    }
+
+    pub fn option_label(&self, row: usize) -> Rect {
+        self.band(OPTIONS_Y + row as f32 * OPTION_ROW_HEIGHT - 50.0, 40.0)
+    }
+
+    // Option `i` of `count` in start screen row `row`, each `spacing` wide
+    pub fn option(&self, row: usize, i: usize, count: usize, spacing: f32) -> Rect {
+        let center_x = DESIGN_WIDTH / 2.0 + (i as f32 - (count as f32 - 1.0) / 2.0) * spacing;
+        self.rect(center_x - spacing / 2.0, OPTIONS_Y + row as f32 * OPTION_ROW_HEIGHT, spacing, 40.0)
+    }
+
+    // Side of one board cell in design pixels, shrinking as boards get larger so they always fit
+    fn design_cell_size(rules: &RuleSet) -> f32 {
+        BOARD_EXTENT / rules.width().max(rules.height()) as f32
+    }
+
+    pub fn cell_size(&self, rules: &RuleSet) -> f32 {
+        Self::design_cell_size(rules) * self.scale
+    }
+
+    fn board_at(&self, rules: &RuleSet, x: f32, y: f32) -> Rect {
+        let cell_size = Self::design_cell_size(rules);
+        self.rect(x, y, rules.width() as f32 * cell_size, rules.height() as f32 * cell_size)
+    }
+
+    fn design_board_size(rules: &RuleSet) -> (f32, f32) {
+        let cell_size = Self::design_cell_size(rules);
+        (rules.width() as f32 * cell_size, rules.height() as f32 * cell_size)
+    }
+
+    // The player's board during the match, left of the middle and centered vertically
+    pub fn player_board(&self, rules: &RuleSet) -> Rect {
+        let (width, height) = Self::design_board_size(rules);
+        self.board_at(rules, DESIGN_WIDTH / 2.0 - width - BOARD_SPACING, (DESIGN_HEIGHT - height) / 2.0)
+    }
+
+    pub fn opponent_board(&self, rules: &RuleSet) -> Rect {
+        let (_width, height) = Self::design_board_size(rules);
+        self.board_at(rules, DESIGN_WIDTH / 2.0 + BOARD_SPACING, (DESIGN_HEIGHT - height) / 2.0)
+    }
+
+    pub fn placement_board(&self, rules: &RuleSet) -> Rect {
+        self.board_at(rules, PLACEMENT_BOARD.0, PLACEMENT_BOARD.1)
+    }
+
+    // The cell of `board` at window position (x, y), if there is one
+    pub fn cell_at(&self, board: Rect, rules: &RuleSet, x: f32, y: f32) -> Option<(usize, usize)> {
+        if !board.contains([x, y]) {
+            return None;
+        }
+        let cell_size = self.cell_size(rules);
+        let row = ((y - board.y) / cell_size) as usize;
+        let col = ((x - board.x) / cell_size) as usize;
+        (row < rules.height() && col < rules.width()).then_some((row, col))
+    }
+
+    // Window rectangle of one cell of `board`
+    pub fn cell(&self, board: Rect, rules: &RuleSet, row: usize, col: usize) -> Rect {
+        let cell_size = self.cell_size(rules);
+        Rect::new(board.x + col as f32 * cell_size, board.y + row as f32 * cell_size, cell_size, cell_size)
+    }
+
+    // The name above a board, clear of the column numbers
+    pub fn board_label(&self, board: Rect) -> Rect {
+        Rect::new(board.x, board.y - 75.0 * self.scale, board.w, 35.0 * self.scale)
+    }
+
+    // Text size of the row letters and column numbers, shrinking with the cells
+    pub fn coordinate_font(&self, rules: &RuleSet) -> f32 {
+        (Self::design_cell_size(rules) * 0.6).min(24.0) * self.scale
+    }
+
+    // The letter left of row `row` of `board`
+    pub fn row_label(&self, board: Rect, rules: &RuleSet, row: usize) -> Rect {
+        let cell = self.cell(board, rules, row, 0);
+        let font = self.coordinate_font(rules);
+        Rect::new(board.x - 35.0 * self.scale, cell.y + (cell.h - font) / 2.0, 30.0 * self.scale, font)
+    }
+
+    // The number above column `col` of `board`
+    pub fn column_label(&self, board: Rect, rules: &RuleSet, col: usize) -> Rect {
+        let cell = self.cell(board, rules, 0, col);
+        let font = self.coordinate_font(rules);
+        Rect::new(cell.x, board.y - font - 5.0 * self.scale, cell.w, font)
+    }
+
+    // Name of the cell under the mouse, top right
+    pub fn hover_readout(&self) -> Rect {
+        self.rect(1300.0, 100.0, 250.0, 40.0)
+    }
+
+    // Entry `i` of a ship list of `count` ships: the name, with the ship model below it
+    pub fn ship_list_entry(&self, list: ShipList, rules: &RuleSet, i: usize, count: usize) -> Rect {
+        let (board_width, board_height) = Self::design_board_size(rules);
+        let boards_y = (DESIGN_HEIGHT - board_height) / 2.0;
+        let (x, y) = match list {
+            ShipList::Player => (DESIGN_WIDTH / 2.0 - board_width - BOARD_SPACING - 250.0, boards_y),
+            ShipList::Opponent => (DESIGN_WIDTH / 2.0 + BOARD_SPACING + board_width + 50.0, boards_y),
+            ShipList::Placement => (PLACEMENT_BOARD.0 + board_width + 50.0, PLACEMENT_BOARD.1),
+        };
+        // Long fleets are packed closer together so they still fit on screen
+        let step = (600.0 / count as f32).min(80.0);
+        self.rect(x, y + i as f32 * step, 240.0, step.min(60.0))
+    }
+
+    // The model of a ship of `size` cells in a ship list entry
+    pub fn ship_model(&self, entry: Rect, size: usize) -> Rect {
+        Rect::new(
+            entry.x,
+            entry.y + 30.0 * self.scale,
+            MODEL_CELL_SIZE * size as f32 * self.scale,
+            MODEL_CELL_SIZE / 2.0 * self.scale,
+        )
+    }
+
+    // Turn and connection status above the boards
+    pub fn status_line(&self, rules: &RuleSet) -> Rect {
+        let board = self.player_board(rules);
+        Rect::new(board.x, self.origin.1 + 100.0 * self.scale, 1000.0 * self.scale, 40.0 * self.scale)
+    }
+
+    // Line `i` of the shot log below the boards
+    pub fn shot_log_line(&self, rules: &RuleSet, i: usize) -> Rect {
+        let board = self.player_board(rules);
+        let y = board.bottom() + (20.0 + i as f32 * 30.0) * self.scale;
+        Rect::new(board.x, y, 600.0 * self.scale, 30.0 * self.scale)
+    }
+
+    pub fn fire_button(&self, rules: &RuleSet) -> Rect {
+        let board = self.opponent_board(rules);
+        Rect::new(board.x, board.bottom() + 20.0 * self.scale, 250.0 * self.scale, 40.0 * self.scale)
+    }
+
+    pub fn instruction(&self) -> Rect {
+        self.rect(20.0, 20.0, DESIGN_WIDTH - 40.0, 40.0)
+    }
+
+    // A button below the board on the placement screen
+    pub fn placement_button(&self, left: f32, width: f32) -> Rect {
+        self.rect(left, PLACEMENT_BUTTONS_Y, width, 40.0)
+    }
+
+    // Why the fleet could not be placed, between the placement buttons and Continue
+    pub fn placement_error(&self) -> Rect {
+        self.rect(100.0, PLACEMENT_BUTTONS_Y + 50.0, 1000.0, 40.0)
+    }
+
+    pub fn continue_button(&self) -> Rect {
+        self.rect(500.0, 700.0, 220.0, 60.0)
+    }
+
+    // Heading of the replay list and lobby screens
+    pub fn screen_title(&self) -> Rect {
+        self.rect(100.0, 50.0, 1000.0, 60.0)
+    }
+
+    // Line `i` of text below a screen title
+    pub fn text_line(&self, i: usize) -> Rect {
+        self.rect(100.0, LIST_Y + i as f32 * 70.0, 1400.0, 40.0)
+    }
+
+    // The replay list with `count` entries
+    pub fn list(&self, count: usize) -> Rect {
+        self.rect(100.0, LIST_Y, 600.0, count as f32 * LIST_STEP)
+    }
+
+    // Height of one list entry
+    pub fn list_step(&self) -> f32 {
+        LIST_STEP * self.scale
+    }
+
+    // A button in the row along the bottom of the screen
+    pub fn bottom_button(&self, left: f32, width: f32) -> Rect {
+        self.rect(left, BOTTOM_ROW_Y, width, 60.0)
+    }
+
+    pub fn back_button(&self) -> Rect {
+        self.bottom_button(100.0, 100.0)
+    }
+
+    pub fn turn_counter(&self) -> Rect {
+        self.bottom_button(1300.0, 250.0)
+    }
+
+    // Backing of the result and stats at the top of the game over screen
+    pub fn game_over_panel(&self) -> Rect {
+        self.band(20.0, 200.0)
+    }
+
+    pub fn game_over_title(&self) -> Rect {
+        self.band(30.0, 70.0)
+    }
+
+    pub fn game_over_stats(&self) -> Rect {
+        self.band(120.0, 40.0)
+    }
+
+    pub fn game_over_verdict(&self) -> Rect {
+        self.band(170.0, 40.0)
+    }
+}
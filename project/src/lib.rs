@@ -0,0 +1,31 @@
+// Game rules for Battleship with no dependency on the ggez front end
+pub mod ai;
+pub mod board;
+pub mod bot;
+pub mod fleet;
+pub mod game;
+pub mod net;
+pub mod notation;
+pub mod replay;
+pub mod rng;
+pub mod rules;
+pub mod save;
+pub mod ship;
+pub mod simulation;
+
+pub use ai::{DensityAi, Difficulty, HuntTargetAi, RandomAi, ShotHistory, ShotStrategy};
+pub use board::{Board, CellState, LayoutStyle, PlacementError, ShotResult};
+pub use bot::{BotCommand, BotError, BotReply, ExternalBot, PendingBot, BOT_PROTOCOL_VERSION};
+pub use fleet::Fleet;
+pub use game::{Game, Player, ShotError, ShotOutcome, Turn};
+pub use net::{
+    check_reveal, Connection, FleetReveal, Listener, Message, NetError, PendingConnection, RemoteEvent, RemotePlayer,
+    Verdict, DEFAULT_PORT, PROTOCOL_VERSION,
+};
+pub use notation::{cell_name, column_name, row_name};
+pub use replay::{load_replay, save_replay, Replay, REPLAY_VERSION};
+pub use rng::MatchRng;
+pub use rules::{GameMode, RuleSet, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+pub use save::{load_game, save_game, SaveError, SAVE_VERSION};
+pub use ship::{Orientation, PlacedShip, ShipId, ShipType};
+pub use simulation::{play_match, simulate, Contestant, MatchReport, ShotStats, Side, SimulationError};
@@ -1,466 +1,645 @@
-use ggez::event::{self, EventHandler};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use battleship::{
+    cell_name, column_name, load_game, load_replay, row_name, save_game, save_replay, Board, Connection, Difficulty,
+    Game, GameMode, LayoutStyle, Listener, Orientation, PendingBot, PendingConnection, Player, RemoteEvent,
+    BotError, RemotePlayer, Replay, RuleSet, ShipType, Turn, Verdict, DEFAULT_PORT,
+};
+use ggez::event::{self, EventHandler, KeyCode, KeyMods};
 use ggez::graphics::{self, Color, DrawMode, Mesh, Rect, Text, TextFragment};
 use ggez::{Context, ContextBuilder, GameResult};
 use ggez::input::mouse::MouseButton;
-use rand::Rng;
-use ggez::mint;
 
-const GRID_SIZE: usize = 10;
-const CELL_SIZE: f32 = 40.0;
-const WIN_CONDITION: usize = 17;
+mod board_view;
+mod layout;
+mod ui;
+
+use board_view::{draw_cells, BoardView};
+use layout::{Layout, MenuItem, ShipList};
+use ui::{Align, Button, Label, ListView, Panel, Ui, Widget};
+
+const BOARD_SIZES: [(usize, usize); 5] = [(8, 8), (10, 10), (12, 12), (15, 10), (20, 20)];
+const MAX_LISTED_REPLAYS: usize = 12;
+const REPLAY_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0]; // Turns per second
+// Window size the game starts with, the layout follows any later resizing
+const WINDOW_WIDTH: f32 = 1600.0;
+const WINDOW_HEIGHT: f32 = 1000.0;
+
+// One row of match options on the start screen, e.g. the difficulties
+struct OptionRow {
+    label: &'static str,
+    options: Vec<String>,
+    selected: usize,
+    spacing: f32,
+}
 
 #[derive(PartialEq)]
 enum GameState {
     StartScreen,
     ShipPlacement,
     Playing,
-}
-
-#[derive(Copy, Clone, PartialEq)]
-enum CellState {
-    Empty,
-    Occupied, // New variant for placed ships
-    Hit,
-    Miss,
-    Hovered,
-}
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-enum ShipType {
-    Destroyer, // Size 2
-    Submarine, // Size 3
-    Cruiser,   // Size 3
-    Battleship, // Size 4
-    Carrier,   // Size 5
-}
-
-
-impl ShipType {
-    fn size(&self) -> usize {
-        match self {
-            ShipType::Destroyer => 2,
-            ShipType::Submarine => 3,
-            ShipType::Cruiser => 3,
-            ShipType::Battleship => 4,
-            ShipType::Carrier => 5,
-        }
-    }
+    ReplayList,
+    Replay,
+    Lobby, // Setting up a LAN match
+    GameOver,
 }
 
 struct BattleshipGame {
-    player_board: Vec<Vec<CellState>>,
-    computer_board: Vec<Vec<CellState>>,
-    is_player_turn: bool,
+    game: Game,
     game_state: GameState,
-    ships_to_place: Vec<ShipType>,
     selected_ship: Option<ShipType>,
     mouse_x: f32,
     mouse_y: f32,
-    is_ship_horizontal: bool, // New field to track ship orientation
-    total_player_hits: usize,
-    total_computer_hits: usize,
+    ship_orientation: Orientation,
+    grab_offset: usize, // Which cell of the held ship stays under the cursor
+    spread_out: bool, // Randomize picks layouts that are hard to find
+    placement_error: Option<String>, // Why the last try to place a fleet failed
+    last_shots: Vec<String>, // Outcome of the latest shot from each side
+    difficulty: Difficulty,
+    rule_presets: Vec<RuleSet>,
+    selected_rules: usize,
+    selected_board_size: usize,
+    mode: GameMode,
+    no_touching: bool,
+    salvo_targets: Vec<(usize, usize)>, // Cells marked for the next salvo
+    has_save: bool, // A match in progress can be continued
+    replay_files: Vec<PathBuf>, // Newest first
+    replay: Option<Replay>,
+    replay_turn: usize, // Turns of the replay shown so far
+    replay_playing: bool,
+    replay_speed: usize, // Index into REPLAY_SPEEDS
+    replay_timer: f32, // Seconds since the last automatic step
+    listener: Option<Listener>, // Hosting a LAN match and waiting for the guest
+    pending: Option<PendingConnection>, // Connecting to the other player in the background
+    remote: Option<RemotePlayer>, // The opponent in a LAN match
+    join_address: String,
+    is_joining: bool,
+    lobby_status: String,
+    match_seconds: f32, // Time spent playing the current match
+    layout: Layout,
+    ui: Ui<BattleshipGame>,
+    widgets_stale: bool, // Something the widgets show changed, they are rebuilt before the next draw
+    player_view: BoardView,
+    opponent_view: BoardView,
+    wants_to_quit: bool, // Quit was picked, the window closes on the next update
+    seed: Option<u64>,   // From --seed: every match against the computer starts from it
+    bot: Option<String>, // From --bot: an external program plays the computer's side
+    pending_bot: Option<PendingBot>, // The bot starting up while the player places their fleet
+}
+
+// Where the match in progress is autosaved, inside the user's data directory
+fn save_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("battleship").join("autosave.txt"))
+}
+
+fn replays_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("battleship").join("replays"))
 }
 
 impl BattleshipGame {
-    fn new() -> Self {
+    fn new(args: Args) -> Self {
         Self {
-            player_board: vec![vec![CellState::Empty; GRID_SIZE]; GRID_SIZE],
-            computer_board: vec![vec![CellState::Empty; GRID_SIZE]; GRID_SIZE],
-            is_player_turn: true,
+            game: Game::default(),
             game_state: GameState::StartScreen,
-            ships_to_place: vec![
-                ShipType::Carrier,
-                ShipType::Battleship,
-                ShipType::Cruiser,
-                ShipType::Submarine,
-                ShipType::Destroyer,
-            ],
             selected_ship: None,
             mouse_x: 0.0,
             mouse_y: 0.0,
-            is_ship_horizontal: true, // Default to horizontal orientation
-            total_player_hits: 0,
-            total_computer_hits: 0,
+            ship_orientation: Orientation::Horizontal,
+            grab_offset: 0,
+            spread_out: false,
+            placement_error: None,
+            last_shots: Vec::new(),
+            difficulty: Difficulty::Medium,
+            rule_presets: RuleSet::presets(),
+            selected_rules: 0,
+            selected_board_size: 1,
+            mode: GameMode::Classic,
+            no_touching: false,
+            salvo_targets: Vec::new(),
+            has_save: save_path().is_some_and(|path| path.exists()),
+            replay_files: Vec::new(),
+            replay: None,
+            replay_turn: 0,
+            replay_playing: false,
+            replay_speed: 1,
+            replay_timer: 0.0,
+            listener: None,
+            pending: None,
+            remote: None,
+            join_address: format!("127.0.0.1:{}", DEFAULT_PORT),
+            is_joining: false,
+            lobby_status: String::new(),
+            match_seconds: 0.0,
+            layout: Layout::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+            ui: Ui::new(),
+            widgets_stale: true,
+            player_view: BoardView::default(),
+            opponent_view: BoardView::default(),
+            wants_to_quit: false,
+            seed: args.seed,
+            bot: args.bot,
+            pending_bot: None,
         }
         
     }
 
-    fn place_computer_ships(&mut self) {
+    // The widgets of the screen being shown, built from the current state of the game
+    fn build_widgets(&self) -> Vec<Widget<Self>> {
+        match self.game_state {
+            GameState::StartScreen => self.start_screen_widgets(),
+            GameState::ShipPlacement => self.placement_widgets(),
+            GameState::Playing | GameState::Replay | GameState::GameOver => self.game_screen_widgets(),
+            GameState::ReplayList => self.replay_list_widgets(),
+            GameState::Lobby => self.lobby_widgets(),
+        }
+    }
 
-        //println!("IN\n");
-        let ships = vec![5, 4, 3, 3, 2,];
-        //let mut not_placed = true;
-        let mut gen = rand::thread_rng();
-        
+    // Rebuilds the widgets if anything they show has changed since they were last built.
+    // Hover, press and focus are kept by the Ui across rebuilds.
+    fn refresh_ui(&mut self) {
+        if !self.widgets_stale {
+            return;
+        }
+        let widgets = self.build_widgets();
+        self.ui.set_widgets(widgets);
+        self.widgets_stale = false;
+    }
 
+    fn start_screen_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let menu_button = |label: &str, item: MenuItem, color: Color| -> Button<Self> {
+            Button::new(label, layout.menu_item(item))
+                .with_size(layout.font(48.0))
+                .with_color(color)
+                .with_align(Align::Center)
+        };
 
-        for selected_ship in &ships {
-            //not_placed = true;
-            let mut can_place = false;
-            let mut col = 0;
-            let mut row = 0;
-            let mut is_ship_horizontal = true;
+        let mut widgets: Vec<Widget<Self>> = vec![
+            Label::new("Battleship", layout.title())
+                .with_size(layout.font(64.0))
+                .with_align(Align::Center)
+                .into(),
+            menu_button("Host LAN", MenuItem::Host, Color::WHITE)
+                .on_click(|game| game.host_network_match())
+                .into(),
+            menu_button("Start", MenuItem::Start, Color::from_rgb(0, 255, 0))
+                .on_click(|game| game.start_match(game.selected_rule_set(), game.difficulty))
+                .into(),
+            menu_button("Join LAN", MenuItem::Join, Color::WHITE)
+                .on_click(|game| game.open_join_lobby())
+                .into(),
+        ];
+        if self.has_save {
+            widgets.push(
+                menu_button("Continue", MenuItem::Continue, Color::from_rgb(0, 200, 255))
+                    .on_click(|game| game.continue_saved_match())
+                    .into(),
+            );
+        }
+        widgets.push(menu_button("Replays", MenuItem::Replays, Color::WHITE).on_click(|game| game.open_replay_list()).into());
+        widgets.push(
+            menu_button("Exit", MenuItem::Exit, Color::from_rgb(255, 0, 0))
+                .on_click(|game| game.wants_to_quit = true)
+                .into(),
+        );
 
-            while !can_place {
-                
-                col = gen.gen_range(0..=GRID_SIZE);
-                row = gen.gen_range(0..=GRID_SIZE);
-                is_ship_horizontal = gen.gen_bool(0.5);
+        for (row_index, row) in self.option_rows().into_iter().enumerate() {
+            widgets.push(
+                Label::new(row.label, layout.option_label(row_index))
+                    .with_size(layout.font(32.0))
+                    .with_align(Align::Center)
+                    .into(),
+            );
 
-                //println!("{col} {row}'n");
-                can_place = if is_ship_horizontal {
-                    if col + selected_ship >= GRID_SIZE {
-                        false
-                    } else {
-                        (0..*selected_ship).all(|i| (col + i) < 10 && row < 10 && self.computer_board[row][col + i] == CellState::Empty)
-                    }
+            let count = row.options.len();
+            for (i, option) in row.options.into_iter().enumerate() {
+                let color = if i == row.selected {
+                    Color::from_rgb(255, 255, 0) // Highlight the chosen option
                 } else {
-                    if row + selected_ship >= GRID_SIZE {
-                        false
-                    } else {
-                        (0..*selected_ship).all(|i| (row + i) < 10 && row < 10 && self.computer_board[row + i][col] == CellState::Empty)
-                    }
+                    Color::from_rgb(200, 200, 200)
                 };
+                widgets.push(
+                    Button::new(option, layout.option(row_index, i, count, row.spacing))
+                        .with_size(layout.font(32.0))
+                        .with_color(color)
+                        .with_align(Align::Center)
+                        .on_click(move |game: &mut Self| game.select_option(row_index, i))
+                        .into(),
+                );
             }
-            
-            
-            if is_ship_horizontal {
-                for i in 0..*selected_ship {
-                    self.computer_board[row][col + i] = CellState::Occupied; // Mark as occupied
-                }
-            } else {
-                for i in 0..*selected_ship {
-                    self.computer_board[row + i][col] = CellState::Occupied; // Mark as occupied
-                }
-            }
+        }
 
-                // // Remove the ship from the list of ships to place
-                // self.ships_to_place.retain(|&s| s != *selected_ship);
+        widgets
+    }
 
-                // // Reset the selected ship after placement
-                // self.selected_ship = None;
-            
-        
-        }
-        
+    // The match options offered on the start screen, top to bottom
+    fn option_rows(&self) -> Vec<OptionRow> {
+        vec![
+            OptionRow {
+                label: "Difficulty",
+                options: Difficulty::ALL.iter().map(|difficulty| format!("{:?}", difficulty)).collect(),
+                selected: Difficulty::ALL.iter().position(|&d| d == self.difficulty).unwrap_or(0),
+                spacing: 200.0,
+            },
+            OptionRow {
+                label: "Fleet",
+                options: self.rule_presets.iter().map(|rules| rules.name().to_string()).collect(),
+                selected: self.selected_rules,
+                spacing: 320.0,
+            },
+            OptionRow {
+                label: "Board",
+                options: BOARD_SIZES.iter().map(|(width, height)| format!("{}x{}", width, height)).collect(),
+                selected: self.selected_board_size,
+                spacing: 180.0,
+            },
+            OptionRow {
+                label: "Mode",
+                options: GameMode::ALL.iter().map(|mode| format!("{:?}", mode)).collect(),
+                selected: GameMode::ALL.iter().position(|&m| m == self.mode).unwrap_or(0),
+                spacing: 200.0,
+            },
+            OptionRow {
+                label: "Ships touching",
+                options: vec!["Allowed".to_string(), "Forbidden".to_string()],
+                selected: self.no_touching as usize,
+                spacing: 240.0,
+            },
+        ]
     }
 
-    fn computer_turn(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut target_row = rng.gen_range(0..GRID_SIZE);
-        let mut target_col = rng.gen_range(0..GRID_SIZE);
-    
-        // Simple logic: if a hit is found, target adjacent cells
-        // for row in 0..GRID_SIZE {
-        //     for col in 0..GRID_SIZE {
-        //         if self.player_board[row][col] == CellState::Hit {
-        //             // Target adjacent cells
-        //             let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-        //             for (dx, dy) in directions.iter() {
-        //                 let new_row = row as isize + dx;
-        //                 let new_col = col as isize + dy;
-        //                 if new_row >= 0 && new_row < GRID_SIZE as isize && new_col >= 0 && new_col < GRID_SIZE as isize {
-        //                     target_row = new_row as usize;
-        //                     target_col = new_col as usize;
-        //                     break;
-        //                 }
-        //             }
-        //         }
-        //     }
-        // }
-    
-        if self.player_board[target_row][target_col] == CellState::Empty {
-            self.player_board[target_row][target_col] = CellState::Miss; 
-        }
-        else if self.player_board[target_row][target_col] == CellState::Occupied {
-            self.player_board[target_row][target_col] = CellState::Hit; 
-            self.total_computer_hits += 1;
-        }
-        else {
-             self.is_player_turn = !self.is_player_turn;
+    // The rules picked with the start screen options
+    fn selected_rule_set(&self) -> RuleSet {
+        let (width, height) = BOARD_SIZES[self.selected_board_size];
+        self.rule_presets[self.selected_rules]
+            .clone()
+            .with_board_size(width, height)
+            .with_mode(self.mode)
+            .with_no_touching(self.no_touching)
+    }
+
+    // Applies a click on option `i` of start screen row `row_index`
+    fn select_option(&mut self, row_index: usize, i: usize) {
+        match row_index {
+            0 => self.difficulty = Difficulty::ALL[i],
+            1 => self.selected_rules = i,
+            2 => self.selected_board_size = i,
+            3 => self.mode = GameMode::ALL[i],
+            4 => self.no_touching = i == 1,
+            _ => {}
         }
+    }
 
-        self.is_player_turn = !self.is_player_turn;
-        //println!("{target_row} {target_col}");
+    fn draw_game_screen(&mut self, ctx: &mut Context) -> GameResult {
+        let layout = self.layout;
+        let rules = self.game.rules();
 
-    }
+        self.player_view.draw(ctx, &layout, rules, self.game.player_board(), layout.player_board(rules), true)?;
+        // Replays and finished matches show both fleets
+        let reveal_computer = matches!(self.game_state, GameState::Replay | GameState::GameOver);
+        let revealed_board = self.revealed_opponent_board();
+        let computer_board = revealed_board.as_ref().unwrap_or(self.game.computer_board());
+        self.opponent_view.draw(ctx, &layout, rules, computer_board, layout.opponent_board(rules), reveal_computer)?;
 
-    fn draw_start_screen(&self, ctx: &mut Context) -> GameResult {
-        //graphics::clear(ctx, Color::from_rgb(0, 0, 180)); // Black background
+        // Draw ship lists
+        self.draw_ship_list(ctx, ShipList::Player)?; // Player's ships on the left
+        self.draw_ship_list(ctx, ShipList::Opponent)?; // Computer's ships on the right
 
-        let title_text = Text::new(
-            TextFragment::new("Battleship")
-                .color(Color::from_rgb(255, 255, 255))
-                .scale(64.0),
-        );
+        // Marked salvo targets on the computer's board
+        if rules.mode() == GameMode::Salvo && self.game_state == GameState::Playing {
+            let color = Color::from_rgba(255, 255, 0, 160); // Yellow with transparency
+            draw_cells(ctx, &layout, rules, layout.opponent_board(rules), &self.salvo_targets, color)?;
+        }
 
-        let start_text = Text::new(
-            TextFragment::new("Start")
-                .color(Color::from_rgb(0, 255, 0))
-                .scale(48.0),
-        );
+        Ok(())
+    }
 
-        let exit_text = Text::new(
-            TextFragment::new("Exit")
-                .color(Color::from_rgb(255, 0, 0))
-                .scale(48.0),
-        );
+    // Board names, turn status, shot log and the buttons of the match, replay and game over screens
+    fn game_screen_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let rules = self.game.rules();
+        let mut widgets: Vec<Widget<Self>> = vec![
+            Label::new("Player", layout.board_label(layout.player_board(rules)))
+                .with_size(layout.font(32.0))
+                .with_align(Align::Center)
+                .into(),
+            Label::new(self.opponent_name(), layout.board_label(layout.opponent_board(rules)))
+                .with_size(layout.font(32.0))
+                .with_align(Align::Center)
+                .into(),
+        ];
+
+        widgets.extend(self.coordinate_labels(layout.player_board(rules)));
+        widgets.extend(self.coordinate_labels(layout.opponent_board(rules)));
+        widgets.extend(self.hover_readout());
+
+        // Outcome of the last shots below the boards
+        for (i, line) in self.last_shots.iter().enumerate() {
+            widgets.push(Label::new(line.as_str(), layout.shot_log_line(rules, i)).with_size(layout.font(24.0)).into());
+        }
 
-        let (window_width, window_height) = (1200.0, 800.0);
-        let title_x = (window_width - title_text.width(ctx) as f32) / 2.0;
-        let title_y = 100.0;
+        match self.game_state {
+            GameState::Playing => {
+                if let Some(remote) = &self.remote {
+                    let status = if !remote.is_opponent_ready() {
+                        "Waiting for the opponent to place their fleet"
+                    } else if self.game.is_player_turn() && !remote.is_awaiting_answer() {
+                        "Your turn"
+                    } else {
+                        "Opponent's turn"
+                    };
+                    widgets.push(Label::new(status, layout.status_line(rules)).with_size(layout.font(32.0)).into());
+                }
+                if rules.mode() == GameMode::Salvo {
+                    widgets.push(self.fire_salvo_button().into());
+                }
+            }
+            GameState::Replay => widgets.extend(self.replay_control_widgets()),
+            GameState::GameOver => widgets.extend(self.game_over_widgets()),
+            _ => {}
+        }
 
-        let start_x = (window_width - start_text.width(ctx) as f32) / 2.0;
-        let start_y = 300.0;
+        widgets
+    }
 
-        let exit_x = (window_width - exit_text.width(ctx) as f32) / 2.0;
-        let exit_y = 400.0;
+    // Row letters left of `board` and column numbers above it
+    fn coordinate_labels(&self, board: Rect) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let rules = self.game.rules();
+        let font = layout.coordinate_font(rules);
+        let rows = (0..rules.height()).map(|row| Label::new(row_name(row), layout.row_label(board, rules, row)));
+        let columns =
+            (0..rules.width()).map(|col| Label::new(column_name(col), layout.column_label(board, rules, col)));
+        rows.chain(columns).map(|label| label.with_size(font).with_align(Align::Center).into()).collect()
+    }
 
-        graphics::draw(ctx, &title_text, graphics::DrawParam::default().dest([title_x, title_y]))?;
-        graphics::draw(ctx, &start_text, graphics::DrawParam::default().dest([start_x, start_y]))?;
-        graphics::draw(ctx, &exit_text, graphics::DrawParam::default().dest([exit_x, exit_y]))?;
+    // The board cell under the mouse, on whichever board is shown
+    fn hovered_cell(&self) -> Option<(usize, usize)> {
+        let layout = self.layout;
+        let rules = self.game.rules();
+        let boards = match self.game_state {
+            GameState::ShipPlacement => vec![layout.placement_board(rules)],
+            GameState::Playing | GameState::Replay | GameState::GameOver => {
+                vec![layout.player_board(rules), layout.opponent_board(rules)]
+            }
+            _ => Vec::new(),
+        };
+        boards.into_iter().find_map(|board| layout.cell_at(board, rules, self.mouse_x, self.mouse_y))
+    }
 
-        Ok(())
+    // Name of the cell under the mouse, so it can be called out
+    fn hover_readout(&self) -> Option<Widget<Self>> {
+        let (row, col) = self.hovered_cell()?;
+        let label = Label::new(format!("Cell: {}", cell_name(row, col)), self.layout.hover_readout());
+        Some(label.with_size(self.layout.font(32.0)).with_color(Color::from_rgb(255, 255, 0)).into())
     }
 
-    fn draw_game_screen(&self, ctx: &mut Context) -> GameResult {
-        let border_color = Color::from_rgb(255, 255, 255);
+    // The opponent's board with their ships showing. In a LAN match they are only
+    // known once the opponent revealed them at the end.
+    fn revealed_opponent_board(&self) -> Option<Board> {
+        let fleet = self.remote.as_ref()?.opponent_fleet()?;
+        let mut board = fleet.board(self.game.rules())?;
+        for &(row, col) in self.game.computer_board().shots() {
+            board.fire(row, col);
+        }
+        Some(board)
+    }
 
-        let (player_board_x, computer_board_x, boards_y) = self.calculate_positions();
+    fn game_over_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let (title, title_color) = if self.game.winner() == Some(Player::Human) {
+            ("You won!", Color::from_rgb(0, 255, 0))
+        } else {
+            ("You lost", Color::from_rgb(255, 0, 0))
+        };
 
-        self.draw_board(ctx, &self.player_board, player_board_x, boards_y, border_color, true)?;
-        self.draw_board(ctx, &self.computer_board, computer_board_x, boards_y, border_color, false)?;
+        let shots = self.game.shots_fired(Player::Human);
+        let accuracy = if shots == 0 { 0.0 } else { 100.0 * self.game.hits(Player::Human) as f32 / shots as f32 };
+        let seconds = self.match_seconds as u64;
+        let mut stats = format!(
+            "Shots fired: {}    Accuracy: {:.0}%    Turns: {}    Time: {}:{:02}",
+            shots,
+            accuracy,
+            self.game.turns_taken(Player::Human),
+            seconds / 60,
+            seconds % 60
+        );
+        // Playing with --seed and the same moves brings the same match back. Over the
+        // network the opponent's moves are not the computer's to repeat.
+        if self.remote.is_none() {
+            stats.push_str(&format!("    Seed: {}", self.game.rng().seed()));
+        }
 
-        self.draw_labels(ctx, player_board_x, computer_board_x, boards_y)?;
+        let mut panel = Panel::new(layout.game_over_panel())
+            .with_border(title_color)
+            .with_child(
+                Label::new(title, layout.game_over_title())
+                    .with_size(layout.font(64.0))
+                    .with_color(title_color)
+                    .with_align(Align::Center),
+            )
+            .with_child(Label::new(stats, layout.game_over_stats()).with_size(layout.font(32.0)).with_align(Align::Center));
+
+        // Whether the opponent's revealed fleet matches the answers they gave
+        if let Some(remote) = &self.remote {
+            let (verdict, verdict_color) = match remote.verdict() {
+                None => ("Checking the opponent's fleet...".to_string(), Color::WHITE),
+                Some(Verdict::Honest) => ("The opponent played fair".to_string(), Color::from_rgb(0, 255, 0)),
+                Some(Verdict::Cheated(reason)) => {
+                    let text = format!("The opponent cheated: {}. No replay was saved", reason);
+                    (text, Color::from_rgb(255, 0, 0))
+                }
+            };
+            panel = panel.with_child(
+                Label::new(verdict, layout.game_over_verdict())
+                    .with_size(layout.font(32.0))
+                    .with_color(verdict_color)
+                    .with_align(Align::Center),
+            );
+        }
+        // A bot that failed during the match lost it there and then
+        if let Some((_, error)) = self.game.forfeit() {
+            panel = panel.with_child(
+                Label::new(format!("The bot forfeited: {}", error), layout.game_over_verdict())
+                    .with_size(layout.font(32.0))
+                    .with_color(Color::from_rgb(255, 255, 0))
+                    .with_align(Align::Center),
+            );
+        }
 
-        // Draw ship lists
-        self.draw_ship_list(ctx, player_board_x - 250.0, boards_y, true)?; // Player's ships on the left
-        self.draw_ship_list(ctx, computer_board_x + GRID_SIZE as f32 * CELL_SIZE + 50.0, boards_y, false)?; // Computer's ships on the right
+        let mut widgets: Vec<Widget<Self>> = vec![panel.into()];
+        // A LAN match cannot be restarted from here, the players have to meet in the lobby again
+        if self.remote.is_none() {
+            widgets.push(
+                Button::new("Rematch", layout.bottom_button(350.0, 200.0))
+                    .with_size(layout.font(48.0))
+                    .on_click(|game: &mut Self| {
+                        let difficulty = game.game.difficulty().unwrap_or(game.difficulty);
+                        game.start_match(game.game.rules().clone(), difficulty);
+                    })
+                    .into(),
+            );
+        }
+        widgets.push(
+            Button::new("Main Menu", layout.bottom_button(650.0, 250.0))
+                .with_size(layout.font(48.0))
+                .on_click(|game: &mut Self| {
+                    if let Some(remote) = game.remote.take() {
+                        remote.leave();
+                    }
+                    game.last_shots.clear();
+                    game.game_state = GameState::StartScreen;
+                })
+                .into(),
+        );
+        widgets.push(
+            Button::new("Quit", layout.bottom_button(1000.0, 120.0))
+                .with_size(layout.font(48.0))
+                .on_click(|game: &mut Self| game.wants_to_quit = true)
+                .into(),
+        );
+        widgets
+    }
 
-        Ok(())
+    // Fires the marked salvo once every shot has a target
+    fn fire_salvo_button(&self) -> Button<Self> {
+        let shots = self.game.shots_per_turn(Player::Human);
+        Button::new(
+            format!("Fire salvo ({}/{})", self.salvo_targets.len(), shots),
+            self.layout.fire_button(self.game.rules()),
+        )
+        .with_size(self.layout.font(32.0))
+        .with_color(Color::from_rgb(0, 255, 0))
+        .with_enabled(self.salvo_targets.len() == shots)
+        .on_click(|game: &mut Self| {
+            let targets = game.salvo_targets.clone();
+            if game.fire_volley(&targets) {
+                game.salvo_targets.clear();
+            }
+        })
     }
 
-    fn draw_ship_list(&self, ctx: &mut Context, x: f32, y: f32, is_player: bool) -> GameResult {
-        let ships = if is_player {
-            vec![
-                ShipType::Carrier,
-                ShipType::Battleship,
-                ShipType::Cruiser,
-                ShipType::Submarine,
-                ShipType::Destroyer,
-            ]
+    fn opponent_name(&self) -> &'static str {
+        if self.game.is_remote() {
+            "Opponent"
         } else {
-            vec![
-                ShipType::Carrier,
-                ShipType::Battleship,
-                ShipType::Cruiser,
-                ShipType::Submarine,
-                ShipType::Destroyer,
-            ]
-        };
+            "Computer"
+        }
+    }
 
-        let mut y_offset = y;
-        for ship in ships {
+    // "Player: B7 hit Carrier, C3 miss" for the shots of one turn
+    fn describe_turn(shooter: &str, turn: &Turn) -> String {
+        let shots: Vec<String> =
+            turn.shots().iter().map(|&(row, col, result)| format!("{} {}", cell_name(row, col), result)).collect();
+        format!("{}: {}", shooter, shots.join(", "))
+    }
+
+    // Adds the turn that was just played to the shot log
+    fn log_last_turn(&mut self, shooter: &str) {
+        if let Some(turn) = self.game.turns().last() {
+            self.last_shots.push(Self::describe_turn(shooter, turn));
+        }
+    }
+
+    fn draw_ship_list(&self, ctx: &mut Context, list: ShipList) -> GameResult {
+        let layout = self.layout;
+        let player = if list == ShipList::Player { Player::Human } else { Player::Computer };
+        // Only reported sinkings count, the opponent's ships may not be known
+        let mut sunk = self.game.sunk_ships(player);
+        let fleet = self.game.rules().fleet();
+
+        for (i, &ship) in fleet.iter().enumerate() {
+            let is_sunk = match sunk.iter().position(|&sunk_ship| sunk_ship == ship) {
+                Some(index) => {
+                    sunk.remove(index);
+                    true
+                }
+                None => false,
+            };
             let ship_text = Text::new(
                 TextFragment::new(format!("{:?} (Size: {})", ship, ship.size()))
                     .color(Color::from_rgb(255, 255, 255))
-                    .scale(24.0),
+                    .scale(layout.font(24.0)),
             );
 
-            graphics::draw(ctx, &ship_text, graphics::DrawParam::default().dest([x, y_offset]))?;
+            let entry = layout.ship_list_entry(list, self.game.rules(), i, fleet.len());
+            draw_at(ctx, &ship_text, entry)?;
 
             // Draw ship model below the text
-            let model_x = x;
-            let model_y = y_offset + 30.0;
-            let model_width = CELL_SIZE * ship.size() as f32;
-            let model_height = CELL_SIZE / 2.0;
-
-            let ship_model = Mesh::new_rectangle(
-                ctx,
-                DrawMode::fill(),
-                Rect::new(model_x, model_y, model_width, model_height),
-                Color::from_rgb(100, 100, 100),
-            )?;
+            let model = layout.ship_model(entry, ship.size());
+            let ship_model = Mesh::new_rectangle(ctx, DrawMode::fill(), model, Color::from_rgb(100, 100, 100))?;
 
             graphics::draw(ctx, &ship_model, graphics::DrawParam::default())?;
 
-            y_offset += 80.0;
-        }
-
-        Ok(())
-    }
-
-    fn calculate_positions(&self) -> (f32, f32, f32) {
-        let window_width = 1600.0; // Updated window width
-        let window_height = 1000.0; // Updated window height
-        let board_width = GRID_SIZE as f32 * CELL_SIZE;
-        let spacing = 100.0; // Increased spacing for better layout
-
-        // Calculate the starting x position for the player's board
-        let player_board_x = (window_width / 2.0) - board_width - spacing;
-
-        // Calculate the starting x position for the computer's board
-        let computer_board_x = (window_width / 2.0) + spacing;
-
-        // Calculate the y position for both boards (centered vertically)
-        let boards_y = (window_height - board_width) / 2.0;
-
-        (player_board_x, computer_board_x, boards_y)
-    }
-
-    fn draw_board(
-        &self,
-        ctx: &mut Context,
-        board: &Vec<Vec<CellState>>,
-        x_offset: f32,
-        y_offset: f32,
-        border_color: Color,
-        is_player_board: bool,
-    ) -> GameResult {
-        for row in 0..GRID_SIZE {
-            for col in 0..GRID_SIZE {
-                let x = x_offset + col as f32 * CELL_SIZE;
-                let y = y_offset + row as f32 * CELL_SIZE;
-                let cell_color:Color;
-
-                
-                if is_player_board{
-                    cell_color = match board[row][col] {
-                        CellState::Empty => Color::from_rgb(0, 128, 255), // Water
-                        CellState::Occupied => Color::from_rgb(100, 100, 100), // Dark grey for occupied cells
-                        CellState::Hit => Color::from_rgb(255, 0, 0),     // Hit
-                        CellState::Miss => Color::from_rgb(255, 255, 255), // Miss
-                        CellState::Hovered => Color::from_rgb(200, 200, 200), 
-                    };
-                }
-                else {
-                    cell_color = match board[row][col] {
-                        CellState::Empty => Color::from_rgb(0, 128, 255), // Water
-                        CellState::Occupied => Color::from_rgb(0, 128, 255), // Dark grey for occupied cells
-                        CellState::Hit => Color::from_rgb(255, 0, 0),     // Hit
-                        CellState::Miss => Color::from_rgb(255, 255, 255), // Miss
-                        CellState::Hovered => Color::from_rgb(200, 200, 200), 
-                    };
-                }
-                
-    
-                let rectangle = Mesh::new_rectangle(
-                    ctx,
-                    DrawMode::fill(),
-                    Rect::new(x, y, CELL_SIZE, CELL_SIZE),
-                    cell_color,
-                )?;
-                graphics::draw(ctx, &rectangle, graphics::DrawParam::default())?;
-    
-                let border = Mesh::new_rectangle(
+            // Cross out sunk ships
+            if is_sunk {
+                let cross_line = Mesh::new_line(
                     ctx,
-                    DrawMode::stroke(1.0),
-                    Rect::new(x, y, CELL_SIZE, CELL_SIZE),
-                    border_color,
+                    &[
+                        ggez::mint::Point2 { x: model.left(), y: model.top() },
+                        ggez::mint::Point2 { x: model.right(), y: model.bottom() },
+                    ],
+                    2.0,
+                    Color::from_rgb(255, 0, 0),
                 )?;
-                graphics::draw(ctx, &border, graphics::DrawParam::default())?;
+                graphics::draw(ctx, &cross_line, graphics::DrawParam::default())?;
             }
         }
-        Ok(())
-    }
-
-    fn draw_labels(&self, ctx: &mut Context, player_board_x: f32, computer_board_x: f32, boards_y: f32) -> GameResult {
-        let label_color = Color::from_rgb(255, 255, 255);
-
-        let player_label = Text::new(
-            TextFragment::new("Player")
-                .color(label_color)
-                .scale(32.0),
-        );
-
-        let computer_label = Text::new(
-            TextFragment::new("Computer")
-                .color(label_color)
-                .scale(32.0),
-        );
-
-        let player_label_x = player_board_x + (GRID_SIZE as f32 * CELL_SIZE) / 2.0 - player_label.width(ctx) as f32 / 2.0;
-        let computer_label_x = computer_board_x + (GRID_SIZE as f32 * CELL_SIZE) / 2.0 - computer_label.width(ctx) as f32 / 2.0;
-
-        graphics::draw(ctx, &player_label, graphics::DrawParam::default().dest([player_label_x, boards_y - 40.0]))?;
-        graphics::draw(ctx, &computer_label, graphics::DrawParam::default().dest([computer_label_x, boards_y - 40.0]))?;
 
         Ok(())
     }
 
-    fn draw_ship_placement_screen(
-        &self,
-        ctx: &mut Context,
-        selected_ship: &mut Option<ShipType>,
-    ) -> GameResult {
-        let instruction = graphics::Text::new(("Place your ships", graphics::Font::default(), 32.0));
-        graphics::draw(ctx, &instruction, (ggez::mint::Point2 { x: 20.0, y: 20.0 }, Color::WHITE))?;
-    
+    fn draw_ship_placement_screen(&mut self, ctx: &mut Context) -> GameResult {
+        let layout = self.layout;
+        let rules = self.game.rules();
         // Draw the player's board
-        let player_board_x = 100.0;
-        let player_board_y = 100.0;
-        self.draw_board(ctx, &self.player_board, player_board_x, player_board_y, Color::WHITE, true)?;
-    
-        // Highlight cells where the ship would be placed
-        if let Some(ship) = selected_ship {
-            let col = ((self.mouse_x - player_board_x) / CELL_SIZE).floor() as usize;
-            let row = ((self.mouse_y - player_board_y) / CELL_SIZE).floor() as usize;
+        let board_area = layout.placement_board(rules);
+        self.player_view.draw(ctx, &layout, rules, self.game.player_board(), board_area, true)?;
     
-            if self.is_ship_horizontal {
-                if col + ship.size() <= GRID_SIZE && row < GRID_SIZE {
-                    for i in 0..ship.size() {
-                        let x = player_board_x + (col + i) as f32 * CELL_SIZE;
-                        let y = player_board_y + row as f32 * CELL_SIZE;
-                        let highlight = Mesh::new_rectangle(
-                            ctx,
-                            DrawMode::fill(),
-                            Rect::new(x, y, CELL_SIZE, CELL_SIZE),
-                            Color::from_rgba(200, 200, 200, 128), // Light grey with transparency
-                        )?;
-                        graphics::draw(ctx, &highlight, graphics::DrawParam::default())?;
-                    }
-                }
+        // Ghost of the held ship under the cursor, in red if it cannot go there
+        if let (Some(ship), Some((row, col))) = (self.selected_ship, self.placement_cell()) {
+            let board = self.game.player_board();
+            let fits = self
+                .held_ship_bow()
+                .is_some_and(|bow| board.can_place(ship, bow, self.ship_orientation));
+            let highlight_color = if fits {
+                Color::from_rgba(200, 200, 200, 128) // Light grey with transparency
             } else {
-                if row + ship.size() <= GRID_SIZE && col < GRID_SIZE {
-                    for i in 0..ship.size() {
-                        let x = player_board_x + col as f32 * CELL_SIZE;
-                        let y = player_board_y + (row + i) as f32 * CELL_SIZE;
-                        let highlight = Mesh::new_rectangle(
-                            ctx,
-                            DrawMode::fill(),
-                            Rect::new(x, y, CELL_SIZE, CELL_SIZE),
-                            Color::from_rgba(200, 200, 200, 128), // Light grey with transparency
-                        )?;
-                        graphics::draw(ctx, &highlight, graphics::DrawParam::default())?;
-                    }
-                }
-            }
+                Color::from_rgba(255, 0, 0, 128) // Red with transparency
+            };
+
+            let cells: Vec<_> = (0..ship.size())
+                .filter_map(|i| {
+                    // Cells before the grabbed one may hang off the top or left edge
+                    let start = if self.ship_orientation.is_horizontal() { col } else { row };
+                    let along = (start + i).checked_sub(self.grab_offset)?;
+                    let cell = if self.ship_orientation.is_horizontal() { (row, along) } else { (along, col) };
+                    board.in_bounds(cell.0, cell.1).then_some(cell)
+                })
+                .collect();
+            draw_cells(ctx, &layout, rules, board_area, &cells, highlight_color)?;
         }
     
         // Draw ship buttons and models
-        let ships_x = player_board_x + GRID_SIZE as f32 * CELL_SIZE + 50.0;
-        let mut ships_y = player_board_y;
-    
-        for ship in &self.ships_to_place {
+        let unplaced = self.game.player_fleet().unplaced();
+        for (i, ship) in unplaced.iter().enumerate() {
             let ship_text = Text::new(
                 TextFragment::new(format!("{:?} (Size: {})", ship, ship.size()))
                     .color(Color::from_rgb(255, 255, 255))
-                    .scale(24.0),
+                    .scale(layout.font(24.0)),
             );
     
-            graphics::draw(ctx, &ship_text, graphics::DrawParam::default().dest([ships_x, ships_y]))?;
+            let entry = layout.ship_list_entry(ShipList::Placement, rules, i, unplaced.len());
+            draw_at(ctx, &ship_text, entry)?;
     
             // Draw ship model below the text
-            let model_x = ships_x;
-            let model_y = ships_y + 30.0;
-            let model_width = CELL_SIZE * ship.size() as f32;
-            let model_height = CELL_SIZE / 2.0;
-    
             let ship_model = Mesh::new_rectangle(
                 ctx,
                 DrawMode::fill(),
-                Rect::new(model_x, model_y, model_width, model_height),
-                if Some(*ship) == *selected_ship {
+                layout.ship_model(entry, ship.size()),
+                if Some(*ship) == self.selected_ship {
                     Color::from_rgb(0, 255, 0) // Highlight selected ship
                 } else {
                     Color::from_rgb(100, 100, 100)
@@ -468,72 +647,614 @@ impl BattleshipGame {
             )?;
     
             graphics::draw(ctx, &ship_model, graphics::DrawParam::default())?;
-    
-            // Cross out placed ships
-            // if !self.ships_to_place.contains(ship) {
-            //     let cross_line = Mesh::new_line(
-            //         ctx,
-            //         &[
-            //             ggez::mint::Point2 { x: model_x, y: model_y },
-            //             ggez::mint::Point2 { x: model_x + model_width, y: model_y + model_height },
-            //         ],
-            //         2.0,
-            //         Color::from_rgb(255, 0, 0),
-            //     )?;
-            //     graphics::draw(ctx, &cross_line, graphics::DrawParam::default())?;
-            // }
-    
-            ships_y += 80.0;
         }
         
-        // Draw "Continue" button
-        if self.ships_to_place.is_empty() {
-            let button_text = Text::new(
-                TextFragment::new("Continue")
-                    .color(Color::from_rgb(0, 255, 0))
-                    .scale(48.0),
+        Ok(())
+    }
+
+    fn placement_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let placement_button = |label: String, left: f32, width: f32| -> Button<Self> {
+            Button::new(label, layout.placement_button(left, width)).with_size(layout.font(32.0))
+        };
+
+        let mut widgets: Vec<Widget<Self>> = vec![
+            Label::new("Place your ships: drag them onto the board, R or right-click rotates", layout.instruction())
+                .with_size(layout.font(32.0))
+                .into(),
+            placement_button("Randomize".to_string(), 100.0, 160.0).on_click(|game| game.randomize_fleet()).into(),
+            placement_button("Clear".to_string(), 300.0, 90.0).on_click(|game| game.clear_fleet()).into(),
+            placement_button(format!("Spread out: {}", if self.spread_out { "On" } else { "Off" }), 430.0, 240.0)
+                .on_click(|game| game.spread_out = !game.spread_out)
+                .into(),
+        ];
+        widgets.extend(self.coordinate_labels(layout.placement_board(self.game.rules())));
+        widgets.extend(self.hover_readout());
+        if let Some(error) = &self.placement_error {
+            widgets.push(
+                Label::new(error.as_str(), layout.placement_error())
+                    .with_size(layout.font(32.0))
+                    .with_color(Color::from_rgb(255, 80, 80))
+                    .into(),
             );
-    
-            let button_x = 500.0;
-            let button_y = 700.0;
-    
-            graphics::draw(ctx, &button_text, graphics::DrawParam::default().dest([button_x, button_y]))?;
         }
-    
-        Ok(())
+
+        if self.game.player_fleet().is_complete() {
+            widgets.push(
+                Button::new("Continue", layout.continue_button())
+                    .with_size(layout.font(48.0))
+                    .with_color(Color::from_rgb(0, 255, 0))
+                    .on_click(|game: &mut Self| game.confirm_fleet())
+                    .into(),
+            );
+        }
+
+        widgets
     }
-    
-    /////////////////////////////////////////////////////////////////////
-    
-    // fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
-    //     self.mouse_x = x;
-    //     self.mouse_y = y;
-    // }
-
-    fn check_for_winner(&mut self, ctx: &mut Context) {
-        if self.total_player_hits == 17 {
-            //GO to win screne
-            println!("WIN");
-            ggez::event::quit(ctx);
+
+    // Starts the match once the player's fleet is placed
+    fn confirm_fleet(&mut self) {
+        if self.pending_bot.is_some() {
+            self.placement_error = Some("Waiting for the bot to place its fleet...".to_string());
+        } else if let Some(remote) = &mut self.remote {
+            // The opponent places their own fleet
+            match remote.send_ready(&self.game) {
+                Ok(()) => self.game_state = GameState::Playing,
+                Err(error) => self.leave_network_match(format!("Connection lost: {}", error)),
+            }
+        } else if self.game.place_computer_ships(LayoutStyle::Random) {
+            self.placement_error = None;
+            self.game_state = GameState::Playing;
+            self.autosave();
+        } else {
+            // Random placement only gives up on boards far too small for the fleet
+            self.placement_error = Some("The computer's fleet does not fit on this board, pick a larger one".to_string());
         }
-        else if self.total_computer_hits == 17{
-            //Go to lose screen
-            println!("LOSE");
-            ggez::event::quit(ctx);
+    }
+
+    fn randomize_fleet(&mut self) {
+        let style = if self.spread_out { LayoutStyle::SpreadOut } else { LayoutStyle::Random };
+        if self.game.place_player_ships(style) {
+            self.selected_ship = None;
+            self.placement_error = None;
+        } else {
+            self.placement_error = Some("No layout of your fleet fits on this board".to_string());
         }
-        
+    }
 
+    fn clear_fleet(&mut self) {
+        self.game.clear_player_ships();
+        self.selected_ship = None;
+        self.placement_error = None;
+    }
+
+    // The cell of the player's board under the mouse on the placement screen
+    fn placement_cell(&self) -> Option<(usize, usize)> {
+        let rules = self.game.rules();
+        self.layout.cell_at(self.layout.placement_board(rules), rules, self.mouse_x, self.mouse_y)
+    }
+
+    // Where the bow of the held ship would go if it was dropped now
+    fn held_ship_bow(&self) -> Option<(usize, usize)> {
+        let (row, col) = self.placement_cell()?;
+        if self.ship_orientation.is_horizontal() {
+            Some((row, col.checked_sub(self.grab_offset)?))
+        } else {
+            Some((row.checked_sub(self.grab_offset)?, col))
+        }
+    }
+
+    // Picks a ship from the list of ships still to place
+    fn hold_ship(&mut self, ship: ShipType) {
+        self.selected_ship = Some(ship);
+        self.grab_offset = 0;
+    }
+
+    // Picks the ship under the mouse back up off the board, holding it by the cell it was grabbed at
+    fn pick_up_ship(&mut self) -> bool {
+        let Some((row, col)) = self.placement_cell() else {
+            return false;
+        };
+        let Some(ship) = self.game.remove_player_ship(row, col) else {
+            return false;
+        };
+
+        self.selected_ship = Some(ship.ship_type());
+        self.ship_orientation = ship.orientation();
+        self.grab_offset = ship.cells().iter().position(|&cell| cell == (row, col)).unwrap_or(0);
+        true
+    }
+
+    // Places the held ship under the mouse. False if it does not fit there, in which
+    // case it stays held.
+    fn drop_held_ship(&mut self) -> bool {
+        let (Some(ship), Some(bow)) = (self.selected_ship, self.held_ship_bow()) else {
+            return false;
+        };
+        if self.game.place_player_ship(ship, bow, self.ship_orientation).is_err() {
+            return false;
+        }
+
+        self.selected_ship = None;
+        self.grab_offset = 0;
+        true
+    }
+
+    // Turns the held ship, or the placed ship under the mouse if none is held. A placed
+    // ship that does not fit the other way round stays as it was.
+    fn rotate_ship(&mut self) {
+        if self.selected_ship.is_some() {
+            self.ship_orientation = self.ship_orientation.flipped();
+        } else if self.pick_up_ship() {
+            self.ship_orientation = self.ship_orientation.flipped();
+            if !self.drop_held_ship() {
+                self.ship_orientation = self.ship_orientation.flipped();
+                self.drop_held_ship();
+            }
+        }
+    }
+
+    fn replay_list_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let mut widgets: Vec<Widget<Self>> =
+            vec![Label::new("Replays", layout.screen_title()).with_size(layout.font(48.0)).into()];
+
+        if self.replay_files.is_empty() {
+            widgets.push(Label::new("No replays yet", layout.text_line(0)).with_size(layout.font(32.0)).into());
+        } else {
+            let names = self
+                .replay_files
+                .iter()
+                .map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default())
+                .collect();
+            widgets.push(
+                ListView::new(names, layout.list(self.replay_files.len()), layout.list_step())
+                    .with_size(layout.font(32.0))
+                    .on_select(|game: &mut Self, i| {
+                        let path = game.replay_files[i].clone();
+                        game.open_replay(&path);
+                    })
+                    .into(),
+            );
+        }
+
+        widgets.push(
+            Self::back_button(layout).on_click(|game| game.game_state = GameState::StartScreen).into(),
+        );
+        widgets
+    }
+
+    fn back_button(layout: Layout) -> Button<Self> {
+        Button::new("Back", layout.back_button())
+            .with_size(layout.font(32.0))
+            .with_color(Color::from_rgb(255, 0, 0))
+    }
+
+    // Replay buttons, left to right, and the turn counter
+    fn replay_control_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let control = |label: String, left: f32, width: f32| -> Button<Self> {
+            Button::new(label, layout.bottom_button(left, width)).with_size(layout.font(32.0))
+        };
+        let turns = self.replay.as_ref().map_or(0, |replay| replay.turns().len());
+
+        vec![
+            Self::back_button(layout)
+                .on_click(|game| {
+                    game.replay = None;
+                    game.last_shots.clear();
+                    game.open_replay_list();
+                })
+                .into(),
+            control("<".to_string(), 560.0, 60.0)
+                .on_click(|game| {
+                    game.replay_playing = false;
+                    game.show_replay_turn(game.replay_turn.saturating_sub(1));
+                })
+                .into(),
+            control(if self.replay_playing { "Pause" } else { "Play" }.to_string(), 640.0, 120.0)
+                .on_click(|game| {
+                    // Playing a finished replay starts it over
+                    if !game.replay_playing && game.is_replay_finished() {
+                        game.show_replay_turn(0);
+                    }
+                    game.replay_playing = !game.replay_playing;
+                    game.replay_timer = 0.0;
+                })
+                .into(),
+            control(">".to_string(), 780.0, 60.0)
+                .on_click(|game| {
+                    game.replay_playing = false;
+                    game.show_replay_turn(game.replay_turn + 1);
+                })
+                .into(),
+            control(format!("Speed {}x", REPLAY_SPEEDS[self.replay_speed]), 860.0, 180.0)
+                .on_click(|game| game.replay_speed = (game.replay_speed + 1) % REPLAY_SPEEDS.len())
+                .into(),
+            Label::new(format!("Turn {}/{}", self.replay_turn, turns), layout.turn_counter())
+                .with_size(layout.font(32.0))
+                .into(),
+        ]
+    }
+
+    // Lists the replay files, newest first. Their names start with the time they were recorded.
+    fn open_replay_list(&mut self) {
+        self.replay_files = replays_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.replay_files.sort();
+        self.replay_files.reverse();
+        self.replay_files.truncate(MAX_LISTED_REPLAYS);
+        self.game_state = GameState::ReplayList;
+    }
+
+    fn open_replay(&mut self, path: &Path) {
+        match load_replay(path) {
+            Ok(replay) => {
+                self.replay = Some(replay);
+                self.replay_playing = false;
+                self.replay_timer = 0.0;
+                self.show_replay_turn(0);
+                self.game_state = GameState::Replay;
+            }
+            Err(error) => eprintln!("Could not load the replay: {}", error),
+        }
+    }
+
+    // Shows the match as it stood after `turn` turns
+    fn show_replay_turn(&mut self, turn: usize) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        let turn = turn.min(replay.turns().len());
+        let Some(game) = replay.game_at(turn) else {
+            return;
+        };
+        let description = turn.checked_sub(1).map(|index| {
+            let last = &replay.turns()[index];
+            let shooter = if last.shooter() == Player::Human { "Player" } else { "Computer" };
+            Self::describe_turn(shooter, last)
+        });
+
+        self.last_shots = description.into_iter().collect();
+        self.game = game;
+        self.replay_turn = turn;
+        self.widgets_stale = true;
+    }
+
+    fn is_replay_finished(&self) -> bool {
+        self.replay.as_ref().is_none_or(|replay| self.replay_turn >= replay.turns().len())
+    }
+
+    fn lobby_widgets(&self) -> Vec<Widget<Self>> {
+        let layout = self.layout;
+        let mut widgets: Vec<Widget<Self>> = vec![
+            Label::new("LAN game", layout.screen_title()).with_size(layout.font(48.0)).into(),
+            Label::new(self.lobby_status.as_str(), layout.text_line(0)).with_size(layout.font(32.0)).into(),
+        ];
+
+        if self.is_joining {
+            widgets.push(
+                Label::new(format!("Address: {}_", self.join_address), layout.text_line(1))
+                    .with_size(layout.font(32.0))
+                    .with_color(Color::from_rgb(255, 255, 0))
+                    .into(),
+            );
+        }
+
+        widgets.push(
+            Self::back_button(layout)
+                .on_click(|game| {
+                    game.listener = None;
+                    game.pending = None;
+                    game.is_joining = false;
+                    game.game_state = GameState::StartScreen;
+                })
+                .into(),
+        );
+        widgets
+    }
+
+    fn open_join_lobby(&mut self) {
+        self.is_joining = true;
+        self.lobby_status = "Type the host's address and press Enter".to_string();
+        self.game_state = GameState::Lobby;
+    }
+
+    // Starts listening for a guest, who plays by the rules picked on the start screen
+    fn host_network_match(&mut self) {
+        self.is_joining = false;
+        self.game_state = GameState::Lobby;
+        match Listener::bind(DEFAULT_PORT) {
+            Ok(listener) => {
+                self.listener = Some(listener);
+                self.lobby_status = format!("Waiting for an opponent on port {}", DEFAULT_PORT);
+            }
+            Err(error) => self.lobby_status = format!("Could not host: {}", error),
+        }
+    }
+
+    fn join_network_match(&mut self) {
+        if self.pending.is_some() {
+            return;
+        }
+        self.lobby_status = format!("Connecting to {}", self.join_address);
+        self.pending = Some(PendingConnection::join(self.join_address.trim()));
+    }
+
+    // Accepts a guest while hosting and starts the match once either side's handshake is done
+    fn poll_lobby(&mut self) {
+        if let Some(pending) = &self.pending {
+            let Some(result) = pending.poll() else {
+                return;
+            };
+            let is_host = pending.is_host();
+            self.pending = None;
+            self.widgets_stale = true;
+            match result {
+                // The host fires first
+                Ok((connection, rules)) => self.start_network_match(connection, rules, is_host),
+                Err(error) if is_host => self.lobby_status = format!("The opponent could not join: {}", error),
+                Err(error) => self.lobby_status = format!("Could not join: {}", error),
+            }
+            return;
+        }
+
+        let accepted = self.listener.as_ref().map(|listener| listener.accept());
+        match accepted {
+            Some(Ok(Some(connection))) => {
+                self.widgets_stale = true;
+                self.lobby_status = "An opponent is joining".to_string();
+                self.pending = Some(PendingConnection::host(connection, self.selected_rule_set()));
+            }
+            Some(Err(error)) => {
+                self.widgets_stale = true;
+                self.listener = None;
+                self.lobby_status = format!("Stopped hosting: {}", error);
+            }
+            _ => {}
+        }
+    }
+
+    fn start_network_match(&mut self, connection: Connection, rules: RuleSet, player_goes_first: bool) {
+        self.listener = None;
+        self.remote = Some(RemotePlayer::new(connection));
+        self.pending_bot = None;
+        self.game = Game::networked(rules, player_goes_first);
+        self.last_shots.clear();
+        self.salvo_targets.clear();
+        self.match_seconds = 0.0;
+        self.placement_error = None;
+        self.game_state = GameState::ShipPlacement;
+    }
+
+    // Drops the LAN match and shows why on the lobby screen
+    fn leave_network_match(&mut self, status: String) {
+        if let Some(remote) = self.remote.take() {
+            remote.leave();
+        }
+        self.listener = None;
+        self.pending = None;
+        self.is_joining = false;
+        self.lobby_status = status;
+        self.game_state = GameState::Lobby;
+    }
+
+    // Handles everything the remote player sent since the last frame
+    fn poll_remote(&mut self) {
+        while let Some(remote) = &mut self.remote {
+            let event = remote.poll(&mut self.game);
+            if !matches!(event, Ok(None)) {
+                self.widgets_stale = true;
+            }
+            match event {
+                Ok(Some(RemoteEvent::Answered(_))) => {
+                    self.last_shots.clear();
+                    self.log_last_turn("Player");
+                }
+                Ok(Some(RemoteEvent::Fired(_))) => self.log_last_turn("Opponent"),
+                Ok(Some(RemoteEvent::Revealed(Verdict::Honest))) => {
+                    let fleet = self.remote.as_ref().and_then(RemotePlayer::opponent_fleet);
+                    if let Some(replay) = fleet.and_then(|fleet| Replay::from_networked_game(&self.game, fleet)) {
+                        self.save_replay(&replay);
+                    }
+                }
+                Ok(Some(RemoteEvent::OpponentReady)) | Ok(Some(RemoteEvent::Revealed(_))) => {}
+                // After the match the verdict says whether leaving hid anything
+                Ok(Some(RemoteEvent::Left)) | Err(_) if self.game.winner().is_some() => break,
+                Ok(Some(RemoteEvent::Left)) => {
+                    self.leave_network_match("The opponent left the match".to_string());
+                }
+                Ok(None) => break,
+                Err(error) => self.leave_network_match(format!("Connection lost: {}", error)),
+            }
+        }
+    }
+
+    // Fires the player's volley: straight at the computer's board, or off to the remote
+    // player, whose answers arrive later. False if the volley could not be fired.
+    fn fire_volley(&mut self, targets: &[(usize, usize)]) -> bool {
+        if let Some(remote) = &mut self.remote {
+            return match remote.fire(&self.game, targets) {
+                Ok(sent) => sent,
+                Err(error) => {
+                    self.leave_network_match(format!("Connection lost: {}", error));
+                    false
+                }
+            };
+        }
+
+        match self.game.fire_salvo(Player::Human, targets) {
+            Ok(_) => {
+                self.last_shots.clear();
+                self.log_last_turn("Player");
+                self.autosave();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Records the finished match in the replays directory
+    fn save_replay(&self, replay: &Replay) {
+        let Some(dir) = replays_dir() else {
+            return;
+        };
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+        let path = dir.join(format!("replay-{}.txt", seconds));
+        if let Err(error) = save_replay(replay, &path) {
+            eprintln!("Could not save the replay: {}", error);
+        }
+    }
+
+    // Saves the match after every turn so a crash does not lose it. A finished match
+    // cannot be continued, so its save is removed instead.
+    fn autosave(&mut self) {
+        // A LAN match cannot be resumed without the other player, nor a bot's match
+        // without the bot
+        if self.game.is_remote() || self.game.difficulty().is_none() {
+            return;
+        }
+        let Some(path) = save_path() else {
+            return;
+        };
+
+        if self.game.winner().is_some() {
+            let _ = fs::remove_file(&path);
+            self.has_save = false;
+        } else if let Err(error) = save_game(&self.game, &path) {
+            eprintln!("Could not save the match: {}", error);
+        } else {
+            self.has_save = true;
+        }
+    }
+
+    fn continue_saved_match(&mut self) {
+        let Some(path) = save_path() else {
+            return;
+        };
+
+        match load_game(&path) {
+            Ok(game) => {
+                self.game = game;
+                self.pending_bot = None;
+                self.last_shots.clear();
+                self.salvo_targets.clear();
+                self.match_seconds = 0.0;
+                self.game_state = GameState::Playing;
+            }
+            Err(error) => {
+                eprintln!("Could not load the saved match: {}", error);
+                self.has_save = false;
+            }
+        }
+    }
+
+    // A new match against the computer, starting with the player's fleet placement. An
+    // external bot takes the computer's seat once it has started and laid out its fleet.
+    fn start_match(&mut self, rules: RuleSet, difficulty: Difficulty) {
+        self.pending_bot = self.bot.as_ref().map(|command| PendingBot::launch(command, &rules));
+        let game = Game::new(rules, difficulty);
+        self.game = match self.seed {
+            Some(seed) => game.with_seed(seed),
+            None => game,
+        };
+        self.last_shots.clear();
+        self.salvo_targets.clear();
+        self.match_seconds = 0.0;
+        self.placement_error = None;
+        self.game_state = GameState::ShipPlacement;
+    }
+
+    // Seats the bot once it has started, or falls back to the computer if it could not
+    fn poll_bot(&mut self) {
+        let Some(result) = self.pending_bot.as_ref().and_then(PendingBot::poll) else {
+            return;
+        };
+        self.pending_bot = None;
+        self.widgets_stale = true;
+        let seated = result.and_then(|(bot, fleet)| {
+            if self.game.set_computer_player(Box::new(bot), fleet) {
+                Ok(())
+            } else {
+                Err(BotError::InvalidFleet("not the fleet the rules ask for".to_string()))
+            }
+        });
+        match seated {
+            Ok(()) => self.placement_error = None,
+            Err(error) => {
+                let message = format!("Could not start the bot, playing the computer instead: {}", error);
+                eprintln!("{}", message);
+                self.placement_error = Some(message);
+            }
+        }
+    }
+
+    fn check_for_winner(&mut self) {
+        if self.game.winner().is_some() {
+            // A LAN match is only recorded once the opponent revealed an honest fleet
+            if !self.game.is_remote() {
+                self.save_replay(&Replay::from_game(&self.game));
+            }
+            self.salvo_targets.clear();
+            self.game_state = GameState::GameOver;
+            self.widgets_stale = true;
+        }
     }
 
 }
 
 impl EventHandler for BattleshipGame {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        if self.game_state == GameState::Playing && !self.is_player_turn {
-            self.computer_turn();
-            self.check_for_winner(ctx);
-            //self.is_player_turn = true;
+        if self.game_state == GameState::Lobby {
+            self.poll_lobby();
+        }
+        if self.game_state == GameState::ShipPlacement {
+            self.poll_bot();
+        }
+
+        if self.game_state == GameState::Playing {
+            self.match_seconds += ggez::timer::delta(ctx).as_secs_f32();
+            if self.remote.is_some() {
+                // The remote player takes the computer's turns
+                self.poll_remote();
+            } else if !self.game.is_player_turn() && self.game.is_computer_ready() {
+                // An external bot's shots are only fired once they came in, so waiting on
+                // the bot does not hold up the frame
+                if self.game.computer_turn().is_ok() {
+                    self.log_last_turn("Computer");
+                    self.autosave();
+                }
+                self.widgets_stale = true;
+            }
+            self.check_for_winner();
+        } else if self.game_state == GameState::GameOver && self.remote.is_some() {
+            // The opponent's fleet is revealed after the last shot
+            self.poll_remote();
+        }
+
+        if self.game_state == GameState::Replay && self.replay_playing {
+            self.replay_timer += ggez::timer::delta(ctx).as_secs_f32();
+            if self.replay_timer >= 1.0 / REPLAY_SPEEDS[self.replay_speed] {
+                self.replay_timer = 0.0;
+                self.show_replay_turn(self.replay_turn + 1);
+                if self.is_replay_finished() {
+                    self.replay_playing = false;
+                }
+            }
+        }
+
+        if self.wants_to_quit {
+            ggez::event::quit(ctx);
         }
+        // Input events of this frame came before this update, so they are covered as well
+        self.refresh_ui();
         Ok(())
     }
 
@@ -543,19 +1264,17 @@ impl EventHandler for BattleshipGame {
         // Clear the screen only once per frame
         graphics::clear(ctx, Color::from_rgb(0, 0, 255)); // Background color
     
-        let mut selected_ship: Option<ShipType> = None;
-        // Based on the game state, draw the correct screen
+        // Based on the game state, draw what the widgets go on top of
         match self.game_state {
-            GameState::StartScreen => {
-                self.draw_start_screen(ctx)?;  // Draw the Start Screen
-            }
             GameState::ShipPlacement => {
-                self.draw_ship_placement_screen(ctx, &mut selected_ship)?;  // Draw Ship Placement Screen
+                self.draw_ship_placement_screen(ctx)?;  // Draw Ship Placement Screen
             }
-            GameState::Playing => {
+            GameState::Playing | GameState::Replay | GameState::GameOver => {
                 self.draw_game_screen(ctx)?;  // Draw the Playing screen (the game itself)
             }
+            GameState::StartScreen | GameState::ReplayList | GameState::Lobby => {}
         }
+        self.ui.draw(ctx)?;
     
         // Present the drawn content to the screen
         graphics::present(ctx)
@@ -568,179 +1287,172 @@ impl EventHandler for BattleshipGame {
         x: f32,
         y: f32,
     ) {
-        if self.game_state == GameState::StartScreen && button == MouseButton::Left {
-            let (window_width, _window_height) = (1200.0, 800.0);
-            let start_x = (window_width - 200.0) / 2.0; // Approximate button width
-            let start_y = 300.0;
-            let exit_y = 400.0;
-    
-            if x >= start_x && x <= start_x + 200.0 && y >= start_y && y <= start_y + 50.0 {
-                self.game_state = GameState::ShipPlacement; // Start game
-            } else if x >= start_x && x <= start_x + 200.0 && y >= exit_y && y <= exit_y + 50.0 {
-                std::process::exit(0); // Exit game
-            }
+        self.widgets_stale = true;
+        // Buttons and lists take the click first, the boards and ship models get the rest
+        if button == MouseButton::Left && self.ui.mouse_down(x, y) {
+            return;
         }
-    
+
+        let layout = self.layout;
+
         if self.game_state == GameState::Playing && button == MouseButton::Left {
-            let (player_board_x, computer_board_x, boards_y) = self.calculate_positions();
-            let board_width = GRID_SIZE as f32 * CELL_SIZE;
-    
-            if x >= computer_board_x && x < computer_board_x + board_width
-                && y >= boards_y && y < boards_y + board_width
-            {
-                let col = ((x - computer_board_x) / CELL_SIZE).floor() as usize;
-                let row = ((y - boards_y) / CELL_SIZE).floor() as usize;
-    
-                if self.computer_board[row][col] == CellState::Empty {
-                    self.computer_board[row][col] = CellState::Miss; 
-                }
-                else if self.computer_board[row][col] == CellState::Occupied {
-                    self.computer_board[row][col] = CellState::Hit; 
-                    self.total_player_hits += 1;
-                }
-                else {
-                    self.is_player_turn = !self.is_player_turn;
+            let rules = self.game.rules();
+            if let Some((row, col)) = layout.cell_at(layout.opponent_board(rules), rules, x, y) {
+                if rules.mode() == GameMode::Salvo {
+                    // Mark or unmark a target for the salvo
+                    if let Some(index) = self.salvo_targets.iter().position(|&cell| cell == (row, col)) {
+                        self.salvo_targets.remove(index);
+                    } else if self.game.computer_board().can_fire(row, col)
+                        && self.salvo_targets.len() < self.game.shots_per_turn(Player::Human)
+                    {
+                        self.salvo_targets.push((row, col));
+                    }
+                } else {
+                    // Clicks on cells that were already fired at are ignored
+                    self.fire_volley(&[(row, col)]);
                 }
-    
-                self.is_player_turn = !self.is_player_turn;
             }
         }
     
         if self.game_state == GameState::ShipPlacement {
             if button == MouseButton::Right {
-                // Toggle ship orientation on right-click
-                if self.selected_ship.is_some() {
-                    self.is_ship_horizontal = !self.is_ship_horizontal;
-                }
+                self.rotate_ship();
             } else if button == MouseButton::Left {
-                let player_board_x = 100.0;
-                let player_board_y = 100.0;
-                let board_width = GRID_SIZE as f32 * CELL_SIZE;
-    
-                let ships_x = player_board_x + board_width + 50.0;
-                let mut ships_y = player_board_y;
-    
+                let rules = self.game.rules();
+                let unplaced = self.game.player_fleet().unplaced();
+
                 // Check if a ship is selected
-                for ship in self.ships_to_place.iter() {
-                    let model_x = ships_x;
-                    let model_y = ships_y + 30.0;
-                    let model_width = CELL_SIZE * ship.size() as f32;
-                    let model_height = CELL_SIZE / 2.0;
-    
-                    if x >= model_x && x <= model_x + model_width
-                        && y >= model_y && y <= model_y + model_height
-                    {
-                        self.selected_ship = Some(*ship); // Set selected_ship here
-                        return;
-                    }
-    
-                    ships_y += 80.0;
-                }
-    
-                // Place ship on the board
-                if let Some(selected_ship) = &self.selected_ship {
-                    let col = ((x - player_board_x) / CELL_SIZE).floor() as usize;
-                    let row = ((y - player_board_y) / CELL_SIZE).floor() as usize;
-    
-                    // Check if the ship can be placed
-                    let can_place = if self.is_ship_horizontal {
-                        if col + selected_ship.size() > GRID_SIZE {
-                            false
-                        } else {
-                            (0..selected_ship.size()).all(|i| self.player_board[row][col + i] == CellState::Empty)
-                        }
-                    } else {
-                        if row + selected_ship.size() > GRID_SIZE {
-                            false
-                        } else {
-                            (0..selected_ship.size()).all(|i| self.player_board[row + i][col] == CellState::Empty)
-                        }
-                    };
-    
-                    if can_place {
-                        if self.is_ship_horizontal {
-                            for i in 0..selected_ship.size() {
-                                self.player_board[row][col + i] = CellState::Occupied; // Mark as occupied
-                            }
-                        } else {
-                            for i in 0..selected_ship.size() {
-                                self.player_board[row + i][col] = CellState::Occupied; // Mark as occupied
-                            }
-                        }
-    
-                        // Remove the ship from the list of ships to place
-                        self.ships_to_place.retain(|&s| s != *selected_ship);
-    
-                        // Reset the selected ship after placement
-                        self.selected_ship = None;
-                    }
+                let clicked = unplaced.iter().enumerate().find_map(|(i, &ship)| {
+                    let entry = layout.ship_list_entry(ShipList::Placement, rules, i, unplaced.len());
+                    layout.ship_model(entry, ship.size()).contains([x, y]).then_some(ship)
+                });
+                if let Some(ship) = clicked {
+                    self.hold_ship(ship);
+                    return;
                 }
-    
-                // Check if the "Continue" button is clicked
-                if self.ships_to_place.is_empty() {
-                    let button_x = 500.0;
-                    let button_y = 700.0;
-                    let button_width = 200.0;
-                    let button_height = 50.0;
-    
-                    if x >= button_x && x <= button_x + button_width
-                        && y >= button_y && y <= button_y + button_height
-                    {
-                        self.place_computer_ships();
-                        self.game_state = GameState::Playing;
-                    }
+
+                // Drop the held ship, or pick up the placed ship under the mouse to move it
+                if self.selected_ship.is_some() {
+                    self.drop_held_ship();
+                } else {
+                    self.pick_up_ship();
                 }
             }
         }
     }
 
-    // fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
-    //     self.mouse_x = x;
-    //     self.mouse_y = y;
+    fn key_down_event(&mut self, _ctx: &mut Context, keycode: KeyCode, keymods: KeyMods, _repeat: bool) {
+        self.widgets_stale = true;
+        if let Some(action) = self.ui.key_down(keycode, keymods) {
+            action(self);
+            return;
+        }
 
-    //     // let board_x = 100.0;
-    //     // let board_y = 100.0;
+        if self.game_state == GameState::ShipPlacement && keycode == KeyCode::R {
+            self.rotate_ship();
+        }
 
-    //     // let col = ((x - board_x) / CELL_SIZE).floor() as usize;
-    //     // let row = ((y - board_y) / CELL_SIZE).floor() as usize;
+        if self.game_state == GameState::Lobby && self.is_joining {
+            match keycode {
+                KeyCode::Back => {
+                    self.join_address.pop();
+                }
+                KeyCode::Return => self.join_network_match(),
+                _ => {}
+            }
+        }
+    }
 
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        if self.game_state == GameState::Lobby && self.is_joining && !character.is_control() {
+            self.join_address.push(character);
+            self.widgets_stale = true;
+        }
+    }
 
-    //     // for r in 0..10 {
-    //     //     for c in 0..10 {
-    //     //         if self.player_board[r][c] != CellState::Hovered {
-    //     //             old_board[r][c] = self.player_board[r][c];
-    //     //         }
-    //     //     }
-    //     // }
+    // Buttons are used when they are let go, and dragged ships are dropped there
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button != MouseButton::Left {
+            return;
+        }
+        self.widgets_stale = true;
+        if let Some(action) = self.ui.mouse_up(x, y) {
+            action(self);
+        }
+        if self.game_state == GameState::ShipPlacement {
+            self.drop_held_ship();
+        }
+    }
 
-    //     // if self.game_state == GameState::ShipPlacement && col < 10 && row < 10  {
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        // Only the cell readout follows the mouse, the Ui works out hovering by itself
+        let hovered = self.hovered_cell();
+        self.mouse_x = x;
+        self.mouse_y = y;
+        self.ui.mouse_motion(x, y);
+        if self.hovered_cell() != hovered {
+            self.widgets_stale = true;
+        }
+    }
 
-    //     //     for r in 0..10 {
-    //     //         for c in 0..10 {
-    //     //             if self.player_board[r][c] == CellState::Hovered {
-    //     //                 self.player_board[r][c] = old_board[r][c]; // Reset to empty or original
-    //     //             }
-    //     //         }
-    //     //     }
+    // Everything is laid out again for the new window size instead of being stretched
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        let _ = graphics::set_screen_coordinates(ctx, Rect::new(0.0, 0.0, width, height));
+        self.layout = Layout::new(width, height);
+        self.widgets_stale = true;
+    }
+}
 
-    //     //     self.player_board[row][col] = CellState::Hovered;
+// Draws `text` at the top left corner of `area`
+fn draw_at(ctx: &mut Context, text: &Text, area: Rect) -> GameResult {
+    graphics::draw(ctx, text, graphics::DrawParam::default().dest([area.x, area.y]))
+}
 
-           
+const USAGE: &str = "Usage: battleship [--seed <number>] [--bot <command>]";
 
-    //     // }
-        
-    //     //println!("{x} {y}\n");
-    // }
+#[derive(Default)]
+struct Args {
+    seed: Option<u64>,
+    bot: Option<String>,
+}
 
-    
+// --seed and --bot, given as "--seed 42" or "--seed=42"
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                let value = args.next().ok_or(format!("{} needs a value", arg))?;
+                (arg, value)
+            }
+        };
+        match name.as_str() {
+            "--seed" => {
+                parsed.seed = Some(value.parse().map_err(|_| format!("the seed must be a whole number, not {}", value))?)
+            }
+            "--bot" => parsed.bot = Some(value),
+            _ => return Err(format!("unknown argument {}", name)),
+        }
+    }
+    Ok(parsed)
 }
 
 fn main() -> GameResult {
+    let args = match parse_args(std::env::args()) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let (ctx, event_loop) = ContextBuilder::new("battleship", "Author Name")
         .window_setup(ggez::conf::WindowSetup::default().title("Battleship"))
-        .window_mode(ggez::conf::WindowMode::default().dimensions(1600.0, 1000.0))
+        .window_mode(ggez::conf::WindowMode::default().dimensions(WINDOW_WIDTH, WINDOW_HEIGHT).resizable(true))
         .build()?;
 
-    let game = BattleshipGame::new();
+    let game = BattleshipGame::new(args);
     event::run(ctx, event_loop, game)
 }
\ No newline at end of file
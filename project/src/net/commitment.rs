@@ -0,0 +1,93 @@
+use sha2::{Digest, Sha256};
+
+use crate::board::Board;
+use crate::fleet::Fleet;
+use crate::game::{Player, Turn};
+use crate::notation::cell_name;
+use crate::rules::RuleSet;
+use crate::save::{placements, ship_line, ShipPlacement};
+use crate::ship::Orientation;
+
+// A player's fleet layout and the random salt that hides it. Only the hash of the two
+// is sent when the fleet is placed, and the layout itself once the match is over, so
+// the opponent cannot move ships around during the match without being caught.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FleetReveal {
+    salt: String,
+    ships: Vec<ShipPlacement>,
+}
+
+impl FleetReveal {
+    // Hides the ships on `board` behind a fresh salt
+    pub fn new(board: &Board) -> Self {
+        let salt: [u8; 16] = rand::random();
+        Self::from_parts(to_hex(&salt), placements(board))
+    }
+
+    pub fn from_parts(salt: String, ships: Vec<ShipPlacement>) -> Self {
+        Self { salt, ships }
+    }
+
+    pub fn salt(&self) -> &str {
+        &self.salt
+    }
+
+    pub fn ships(&self) -> &[ShipPlacement] {
+        &self.ships
+    }
+
+    // The SHA-256 hash of the salt and the ships as they are written in save files, in hex
+    pub fn commitment(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        for &ship in &self.ships {
+            hasher.update(b"\n");
+            hasher.update(ship_line("ship", ship).as_bytes());
+        }
+        to_hex(&hasher.finalize())
+    }
+
+    // The revealed fleet on a board of its own. None if it is not a legal layout of
+    // exactly the fleet the rules ask for.
+    pub fn board(&self, rules: &RuleSet) -> Option<Board> {
+        let mut fleet = Fleet::new(rules);
+        for &(ship, row, col, horizontal) in &self.ships {
+            fleet.place(ship, (row, col), Orientation::from_horizontal(horizontal)).ok()?;
+        }
+        fleet.is_complete().then(|| fleet.into_board())
+    }
+}
+
+// Whether the opponent played fair, as far as their revealed fleet shows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Honest,
+    Cheated(String),
+}
+
+// Checks the opponent's revealed fleet against the commitment they sent when they
+// placed it, and every answer they gave to the player's shots against that fleet
+pub fn check_reveal(commitment: &str, reveal: &FleetReveal, rules: &RuleSet, turns: &[Turn]) -> Verdict {
+    if reveal.commitment() != commitment {
+        return Verdict::Cheated("the revealed fleet is not the one they placed".to_string());
+    }
+    let Some(mut board) = reveal.board(rules) else {
+        return Verdict::Cheated("the revealed fleet breaks the rules".to_string());
+    };
+
+    let player_shots = turns.iter().filter(|turn| turn.shooter() == Player::Human);
+    for &(row, col, answer) in player_shots.flat_map(|turn| turn.shots()) {
+        if board.fire(row, col) != Some(answer) {
+            return Verdict::Cheated(format!("they answered {:?} to the shot at {}", answer, cell_name(row, col)));
+        }
+    }
+    Verdict::Honest
+}
+
+pub(crate) fn is_commitment(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
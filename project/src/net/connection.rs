@@ -0,0 +1,263 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use crate::rules::RuleSet;
+
+use super::protocol::{Message, PROTOCOL_VERSION};
+use super::NetError;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// One side of a TCP link to the other player. Incoming messages are read on a
+// background thread, so checking for them never blocks the game loop.
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Receiver<Result<Message, NetError>>,
+}
+
+impl Connection {
+    pub fn connect(address: &str) -> Result<Self, NetError> {
+        let mut last_error = NetError::Protocol(format!("no address found for {:?}", address));
+        for socket_address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT) {
+                Ok(stream) => return Self::new(stream),
+                Err(error) => last_error = error.into(),
+            }
+        }
+        Err(last_error)
+    }
+
+    fn new(stream: TcpStream) -> Result<Self, NetError> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = line.map_err(NetError::from).and_then(|line| Message::decode(&line));
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        Ok(Self { stream, incoming })
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        writeln!(self.stream, "{}", message.encode())?;
+        Ok(())
+    }
+
+    // The next message if one has arrived
+    pub fn try_recv(&self) -> Result<Option<Message>, NetError> {
+        match self.incoming.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(NetError::Disconnected),
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Message, NetError> {
+        match self.incoming.recv_timeout(timeout) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => Err(NetError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(NetError::Disconnected),
+        }
+    }
+
+    // The host waits for the guest's greeting, answers it and sends the rules of the match
+    pub fn host_handshake(&mut self, rules: &RuleSet) -> Result<(), NetError> {
+        let version = match self.recv_timeout(HANDSHAKE_TIMEOUT)? {
+            Message::Hello(version) => version,
+            other => return Err(NetError::Protocol(format!("expected HELLO, got {:?}", other))),
+        };
+
+        self.send(&Message::Hello(PROTOCOL_VERSION))?;
+        if version != PROTOCOL_VERSION {
+            return Err(NetError::VersionMismatch(version));
+        }
+        self.send(&Message::Rules(rules.clone()))
+    }
+
+    // The guest greets the host and learns the rules of the match
+    pub fn guest_handshake(&mut self) -> Result<RuleSet, NetError> {
+        self.send(&Message::Hello(PROTOCOL_VERSION))?;
+        match self.recv_timeout(HANDSHAKE_TIMEOUT)? {
+            Message::Hello(PROTOCOL_VERSION) => {}
+            Message::Hello(version) => return Err(NetError::VersionMismatch(version)),
+            other => return Err(NetError::Protocol(format!("expected HELLO, got {:?}", other))),
+        }
+
+        match self.recv_timeout(HANDSHAKE_TIMEOUT)? {
+            Message::Rules(rules) => Ok(rules),
+            other => Err(NetError::Protocol(format!("expected RULES, got {:?}", other))),
+        }
+    }
+}
+
+// Closing the socket also stops the reader thread and tells the other side
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// A connection being opened or set up on a worker thread, so the game loop keeps
+// running while it waits for the other side
+pub struct PendingConnection {
+    is_host: bool,
+    result: Receiver<Result<(Connection, RuleSet), NetError>>,
+}
+
+impl PendingConnection {
+    // Connects to the host at `address` and learns the rules of the match
+    pub fn join(address: &str) -> Self {
+        let address = address.to_string();
+        Self::spawn(false, move || {
+            let mut connection = Connection::connect(&address)?;
+            let rules = connection.guest_handshake()?;
+            Ok((connection, rules))
+        })
+    }
+
+    // Greets a guest who just connected and sends them `rules`
+    pub fn host(mut connection: Connection, rules: RuleSet) -> Self {
+        Self::spawn(true, move || {
+            connection.host_handshake(&rules)?;
+            Ok((connection, rules))
+        })
+    }
+
+    fn spawn(is_host: bool, work: impl FnOnce() -> Result<(Connection, RuleSet), NetError> + Send + 'static) -> Self {
+        let (sender, result) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(work());
+        });
+        Self { is_host, result }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.is_host
+    }
+
+    // The connection and the rules of the match once it is set up
+    pub fn poll(&self) -> Option<Result<(Connection, RuleSet), NetError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(NetError::Disconnected)),
+        }
+    }
+}
+
+// Waits for a guest to join a hosted match without blocking the game loop
+pub struct Listener {
+    listener: TcpListener,
+}
+
+impl Listener {
+    // Port 0 picks any free port
+    pub fn bind(port: u16) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    // The guest's connection if one has arrived
+    pub fn accept(&self) -> Result<Option<Connection>, NetError> {
+        match self.listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                Connection::new(stream).map(Some)
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rules::GameMode;
+
+    // A host listening on a free port and a guest connected to it, both handshakes
+    // still to do
+    fn loopback() -> (Connection, Connection) {
+        let listener = Listener::bind(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let guest = Connection::connect(&format!("127.0.0.1:{}", port)).unwrap();
+        loop {
+            if let Some(host) = listener.accept().unwrap() {
+                return (host, guest);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn handshake_over_loopback() {
+        let (mut host, mut guest) = loopback();
+        let rules = RuleSet::russian().with_mode(GameMode::Salvo);
+
+        let host_rules = rules.clone();
+        let hosting = thread::spawn(move || host.host_handshake(&host_rules).map(|()| host));
+        assert_eq!(guest.guest_handshake().unwrap(), rules);
+        let mut host = hosting.join().unwrap().unwrap();
+
+        host.send(&Message::Fire(vec![(1, 2)])).unwrap();
+        assert_eq!(guest.recv_timeout(HANDSHAKE_TIMEOUT).unwrap(), Message::Fire(vec![(1, 2)]));
+        drop(host);
+        assert!(matches!(guest.recv_timeout(HANDSHAKE_TIMEOUT), Err(NetError::Disconnected)));
+    }
+
+    #[test]
+    fn guest_rejects_another_version() {
+        let (mut host, mut guest) = loopback();
+        let hosting = thread::spawn(move || {
+            host.recv_timeout(HANDSHAKE_TIMEOUT).unwrap();
+            host.send(&Message::Hello(PROTOCOL_VERSION + 1)).unwrap();
+            host
+        });
+        let result = guest.guest_handshake();
+        assert!(matches!(result, Err(NetError::VersionMismatch(version)) if version == PROTOCOL_VERSION + 1));
+        hosting.join().unwrap();
+    }
+
+    #[test]
+    fn pending_connections_meet_in_the_background() {
+        let listener = Listener::bind(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let rules = RuleSet::classic().with_board_size(12, 12);
+
+        let joining = PendingConnection::join(&format!("127.0.0.1:{}", port));
+        let hosting = loop {
+            if let Some(connection) = listener.accept().unwrap() {
+                break PendingConnection::host(connection, rules.clone());
+            }
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert!(hosting.is_host() && !joining.is_host());
+
+        for pending in [hosting, joining] {
+            let result = loop {
+                if let Some(result) = pending.poll() {
+                    break result;
+                }
+                thread::sleep(Duration::from_millis(1));
+            };
+            assert_eq!(result.unwrap().1, rules);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+mod commitment;
+mod connection;
+mod protocol;
+mod remote;
+
+pub use commitment::{check_reveal, FleetReveal, Verdict};
+pub use connection::{Connection, Listener, PendingConnection};
+pub use protocol::{Message, PROTOCOL_VERSION};
+pub use remote::{RemoteEvent, RemotePlayer};
+
+pub(crate) use protocol::encode_rules;
+
+use std::fmt;
+use std::io;
+
+use crate::save::SaveError;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    // The other side sent something this version does not understand
+    Protocol(String),
+    VersionMismatch(u32),
+    Timeout,
+    Disconnected,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetError::Io(error) => write!(f, "{}", error),
+            NetError::Protocol(reason) => write!(f, "protocol error: {}", reason),
+            NetError::VersionMismatch(version) => {
+                write!(f, "the other side speaks protocol version {}, not {}", version, PROTOCOL_VERSION)
+            }
+            NetError::Timeout => write!(f, "the other side did not answer in time"),
+            NetError::Disconnected => write!(f, "the other side left"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> Self {
+        NetError::Io(error)
+    }
+}
+
+impl From<SaveError> for NetError {
+    fn from(error: SaveError) -> Self {
+        NetError::Protocol(error.to_string())
+    }
+}
@@ -0,0 +1,154 @@
+use crate::board::ShotResult;
+use crate::rules::{GameMode, RuleSet};
+use crate::save::{
+    format_placement, format_result, join, parse_cell, parse_name, parse_placement, parse_result, parse_value,
+};
+use crate::ship::ShipType;
+
+use super::commitment::{is_commitment, FleetReveal};
+use super::NetError;
+
+// Bump whenever a message changes. Both sides must speak the same version.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// Everything the two sides of a networked match say to each other, one message per line:
+//
+//   HELLO 2
+//   RULES 10 10 Classic false Carrier,Battleship,Cruiser,Submarine,Destroyer Classic
+//   READY 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08
+//   FIRE 3,4 5,6
+//   RESULTS Miss Hit:Carrier
+//   REVEAL 5e0c8ac3f1b0d7a2 Carrier,0,0,horizontal Destroyer,5,2,vertical
+//   BYE
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hello(u32),
+    // Sent by the host, who picks the rules for both sides
+    Rules(RuleSet),
+    // The sender has placed their fleet, and commits to it with its hash
+    Ready(String),
+    Fire(Vec<(usize, usize)>),
+    // Answers to the last FIRE, in the same order
+    Results(Vec<ShotResult>),
+    // The sender's fleet and salt, once the match is over
+    Reveal(FleetReveal),
+    Bye,
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        match self {
+            Message::Hello(version) => format!("HELLO {}", version),
+            Message::Rules(rules) => format!("RULES {}", encode_rules(rules)),
+            Message::Ready(commitment) => format!("READY {}", commitment),
+            Message::Fire(targets) => {
+                format!("FIRE {}", join(targets.iter().map(|(row, col)| format!("{},{}", row, col))))
+            }
+            Message::Results(results) => {
+                format!("RESULTS {}", join(results.iter().map(|&result| format_result(result))))
+            }
+            Message::Reveal(reveal) => {
+                let ships = reveal.ships().iter().map(|&ship| format_placement(ship));
+                format!("REVEAL {} {}", reveal.salt(), join(ships)).trim_end().to_string()
+            }
+            Message::Bye => "BYE".to_string(),
+        }
+    }
+
+    pub fn decode(line: &str) -> Result<Self, NetError> {
+        let (command, rest) = line.trim_end().split_once(' ').unwrap_or((line.trim_end(), ""));
+        let message = match command {
+            "HELLO" => Message::Hello(parse_value(rest)?),
+            "RULES" => Message::Rules(decode_rules(rest)?),
+            "READY" if is_commitment(rest) => Message::Ready(rest.to_string()),
+            "FIRE" => Message::Fire(rest.split_whitespace().map(parse_cell).collect::<Result<_, _>>()?),
+            "RESULTS" => Message::Results(rest.split_whitespace().map(parse_result).collect::<Result<_, _>>()?),
+            "REVEAL" => Message::Reveal(decode_reveal(rest)?),
+            "BYE" => Message::Bye,
+            _ => return Err(NetError::Protocol(format!("unknown message {:?}", line))),
+        };
+        Ok(message)
+    }
+}
+
+// Width, height, mode, no-touching, fleet and name, e.g.
+// "10 10 Classic false Carrier,Battleship,Cruiser,Submarine,Destroyer Classic"
+pub(crate) fn encode_rules(rules: &RuleSet) -> String {
+    format!(
+        "{} {} {:?} {} {} {}",
+        rules.width(),
+        rules.height(),
+        rules.mode(),
+        rules.no_touching(),
+        rules.fleet().iter().map(|ship| format!("{:?}", ship)).collect::<Vec<_>>().join(","),
+        rules.name()
+    )
+}
+
+fn decode_rules(value: &str) -> Result<RuleSet, NetError> {
+    let parts: Vec<&str> = value.splitn(6, ' ').collect();
+    let [width, height, mode, no_touching, fleet, name] = parts[..] else {
+        return Err(NetError::Protocol(format!("bad rules {:?}", value)));
+    };
+
+    let fleet = fleet
+        .split(',')
+        .map(|ship| parse_name(&ShipType::ALL, ship))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RuleSet::new(name, fleet)
+        .with_board_size(parse_value(width)?, parse_value(height)?)
+        .with_mode(parse_name(&GameMode::ALL, mode)?)
+        .with_no_touching(parse_value(no_touching)?))
+}
+
+fn decode_reveal(value: &str) -> Result<FleetReveal, NetError> {
+    let mut parts = value.split_whitespace();
+    let salt = parts.next().ok_or_else(|| NetError::Protocol("REVEAL without a salt".to_string()))?;
+    let ships = parts.map(parse_placement).collect::<Result<_, _>>()?;
+    Ok(FleetReveal::from_parts(salt.to_string(), ships))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let reveal = FleetReveal::from_parts(
+            "5e0c8ac3f1b0d7a2".to_string(),
+            vec![(ShipType::Carrier, 0, 0, true), (ShipType::Destroyer, 5, 2, false)],
+        );
+        let messages = [
+            Message::Hello(PROTOCOL_VERSION),
+            Message::Rules(RuleSet::classic()),
+            Message::Rules(RuleSet::russian().with_board_size(12, 8).with_mode(GameMode::Salvo).with_no_touching(true)),
+            Message::Ready("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string()),
+            Message::Fire(vec![(3, 4), (5, 6)]),
+            Message::Results(vec![
+                ShotResult::Miss,
+                ShotResult::Hit(ShipType::Carrier),
+                ShotResult::Sunk(ShipType::Cruiser),
+            ]),
+            Message::Reveal(reveal),
+            Message::Reveal(FleetReveal::from_parts("00".to_string(), Vec::new())),
+            Message::Bye,
+        ];
+
+        for message in messages {
+            assert_eq!(Message::decode(&message.encode()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn rules_keep_a_name_with_spaces() {
+        let rules = RuleSet::new("House rules", vec![ShipType::Carrier]);
+        assert_eq!(Message::decode(&Message::Rules(rules.clone()).encode()).unwrap(), Message::Rules(rules));
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        for line in ["HELLO two", "READY abc", "FIRE 3", "RESULTS Hit:Rowboat", "RULES 10 10 Classic", "SHOUT hi"] {
+            assert!(matches!(Message::decode(line), Err(NetError::Protocol(_))), "{:?} was accepted", line);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::board::ShotResult;
+use crate::game::{Game, Player, ShotOutcome};
+
+use super::commitment::{check_reveal, FleetReveal, Verdict};
+use super::connection::Connection;
+use super::protocol::Message;
+use super::NetError;
+
+// What happened when the remote player's messages were handled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteEvent {
+    // The opponent has placed their fleet
+    OpponentReady,
+    // The opponent answered the player's volley
+    Answered(Vec<ShotResult>),
+    // The opponent fired a volley at the player's board
+    Fired(Vec<ShotResult>),
+    // The match is over and the opponent's fleet was checked
+    Revealed(Verdict),
+    Left,
+}
+
+// The opponent in a networked match. It takes the place of the computer's strategy:
+// the player's volleys are sent to it, and its volleys are fired at the player's board.
+pub struct RemotePlayer {
+    connection: Connection,
+    // The player's volley that has not been answered yet
+    awaiting: Option<Vec<(usize, usize)>>,
+    // The player's fleet, kept hidden until the match is over
+    fleet: Option<FleetReveal>,
+    has_revealed: bool,
+    // The hash the opponent sent with READY
+    opponent_commitment: Option<String>,
+    opponent_fleet: Option<FleetReveal>,
+    verdict: Option<Verdict>,
+}
+
+impl RemotePlayer {
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            awaiting: None,
+            fleet: None,
+            has_revealed: false,
+            opponent_commitment: None,
+            opponent_fleet: None,
+            verdict: None,
+        }
+    }
+
+    // Tells the opponent the player's fleet is placed and commits to it
+    pub fn send_ready(&mut self, game: &Game) -> Result<(), NetError> {
+        let fleet = FleetReveal::new(game.player_board());
+        self.connection.send(&Message::Ready(fleet.commitment()))?;
+        self.fleet = Some(fleet);
+        Ok(())
+    }
+
+    pub fn is_opponent_ready(&self) -> bool {
+        self.opponent_commitment.is_some()
+    }
+
+    // The opponent's fleet, once they revealed it at the end of the match
+    pub fn opponent_fleet(&self) -> Option<&FleetReveal> {
+        self.opponent_fleet.as_ref()
+    }
+
+    // None until the opponent's fleet has been revealed and checked
+    pub fn verdict(&self) -> Option<&Verdict> {
+        self.verdict.as_ref()
+    }
+
+    pub fn is_awaiting_answer(&self) -> bool {
+        self.awaiting.is_some()
+    }
+
+    // Sends the player's volley. Returns false and sends nothing if it is not the
+    // player's turn to fire it.
+    pub fn fire(&mut self, game: &Game, targets: &[(usize, usize)]) -> Result<bool, NetError> {
+        if !self.is_opponent_ready() || self.awaiting.is_some() || !game.is_valid_salvo(Player::Human, targets) {
+            return Ok(false);
+        }
+
+        self.connection.send(&Message::Fire(targets.to_vec()))?;
+        self.awaiting = Some(targets.to_vec());
+        Ok(true)
+    }
+
+    // Handles the next message from the opponent, if any has arrived
+    pub fn poll(&mut self, game: &mut Game) -> Result<Option<RemoteEvent>, NetError> {
+        let message = match self.connection.try_recv() {
+            Ok(Some(message)) => message,
+            Ok(None) => return Ok(None),
+            // Once the match is over, losing the connection is the same as leaving
+            Err(_) if game.winner().is_some() => {
+                self.left_after_match();
+                return Ok(Some(RemoteEvent::Left));
+            }
+            Err(error) => return Err(error),
+        };
+
+        let event = match message {
+            Message::Ready(commitment) => {
+                if self.opponent_commitment.is_some() {
+                    return Err(NetError::Protocol("READY sent twice".to_string()));
+                }
+                self.opponent_commitment = Some(commitment);
+                RemoteEvent::OpponentReady
+            }
+            Message::Fire(targets) => {
+                let outcomes = game
+                    .fire_salvo(Player::Computer, &targets)
+                    .map_err(|error| NetError::Protocol(format!("volley {:?} is not allowed: {}", targets, error)))?;
+                let results: Vec<ShotResult> = outcomes.iter().map(ShotOutcome::result).collect();
+                self.connection.send(&Message::Results(results.clone()))?;
+                RemoteEvent::Fired(results)
+            }
+            Message::Results(results) => {
+                let targets = self
+                    .awaiting
+                    .take()
+                    .ok_or_else(|| NetError::Protocol("answers to a volley that was not fired".to_string()))?;
+                if !game.record_player_results(&targets, &results) {
+                    return Err(NetError::Protocol(format!("answers {:?} do not fit the volley", results)));
+                }
+                RemoteEvent::Answered(results)
+            }
+            Message::Reveal(fleet) => {
+                let (Some(commitment), Some(_)) = (&self.opponent_commitment, game.winner()) else {
+                    return Err(NetError::Protocol("REVEAL before the match is over".to_string()));
+                };
+                let verdict = check_reveal(commitment, &fleet, game.rules(), game.turns());
+                self.opponent_fleet = Some(fleet);
+                self.verdict = Some(verdict.clone());
+                RemoteEvent::Revealed(verdict)
+            }
+            Message::Bye => {
+                if game.winner().is_some() {
+                    self.left_after_match();
+                }
+                RemoteEvent::Left
+            }
+            other => return Err(NetError::Protocol(format!("unexpected {:?} during the match", other))),
+        };
+
+        if game.winner().is_some() && event != RemoteEvent::Left {
+            self.reveal()?;
+        }
+        Ok(Some(event))
+    }
+
+    // Leaving at the end without a word about the fleet hides any cheating
+    fn left_after_match(&mut self) {
+        if self.verdict.is_none() {
+            self.verdict = Some(Verdict::Cheated("they left without revealing their fleet".to_string()));
+        }
+    }
+
+    // Shows the opponent the player's fleet once the match is over
+    fn reveal(&mut self) -> Result<(), NetError> {
+        if self.has_revealed {
+            return Ok(());
+        }
+        let fleet = self.fleet.clone().ok_or_else(|| NetError::Protocol("the fleet was never placed".to_string()))?;
+        self.connection.send(&Message::Reveal(fleet))?;
+        self.has_revealed = true;
+        Ok(())
+    }
+
+    // Says goodbye before the connection is dropped
+    pub fn leave(mut self) {
+        let _ = self.connection.send(&Message::Bye);
+    }
+}
@@ -0,0 +1,15 @@
+// The names players call cells by: rows are letters from A and columns are numbers
+// from 1, so the cell in row 1, column 6 is "B7". Boards are at most MAX_BOARD_SIZE
+// rows high, which stays well within the alphabet.
+
+pub fn row_name(row: usize) -> String {
+    char::from(b'A' + row as u8).to_string()
+}
+
+pub fn column_name(col: usize) -> String {
+    (col + 1).to_string()
+}
+
+pub fn cell_name(row: usize, col: usize) -> String {
+    format!("{}{}", row_name(row), column_name(col))
+}
@@ -0,0 +1,225 @@
+use std::fs;
+use std::path::Path;
+
+use crate::ai::Difficulty;
+use crate::board::ShotResult;
+use crate::game::{Game, Player, Turn};
+use crate::net::FleetReveal;
+use crate::rng::MatchRng;
+use crate::rules::RuleSet;
+use crate::save::{
+    corrupt, format_result, join, parse_cell, parse_name, parse_result, placements, rules_lines, ship_line,
+    write_file, Fields, SaveData, SaveError, ShipPlacement,
+};
+
+pub const REPLAY_VERSION: u32 = 1;
+const REPLAY_HEADER: &str = "battleship-replay";
+
+// A finished match as the ordered log of both fleets' placements and every turn,
+// with the answers each shot got
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    rules: RuleSet,
+    difficulty: Option<Difficulty>,
+    player_ships: Vec<ShipPlacement>,
+    computer_ships: Vec<ShipPlacement>,
+    turns: Vec<Turn>,
+}
+
+impl Replay {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            rules: game.rules().clone(),
+            difficulty: game.difficulty(),
+            player_ships: placements(game.player_board()),
+            computer_ships: placements(game.computer_board()),
+            turns: game.turns().to_vec(),
+        }
+    }
+
+    // A LAN match, with the opponent's fleet as they revealed it at the end. None if
+    // that fleet does not give the answers they sent during the match.
+    pub fn from_networked_game(game: &Game, opponent: &FleetReveal) -> Option<Self> {
+        let replay = Self {
+            computer_ships: opponent.ships().to_vec(),
+            ..Self::from_game(game)
+        };
+        replay.matches_its_fleets().then_some(replay)
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    // The match as it stood after its first `count` turns
+    pub fn game_at(&self, count: usize) -> Option<Game> {
+        let played = &self.turns[..count.min(self.turns.len())];
+        let shots_by = |shooter: Player| {
+            played
+                .iter()
+                .filter(|turn| turn.shooter() == shooter)
+                .flat_map(|turn| turn.shots().iter().map(|&(row, col, _)| (row, col)))
+                .collect()
+        };
+
+        Game::restore(SaveData {
+            rules: self.rules.clone(),
+            difficulty: self.difficulty,
+            player_ships: self.player_ships.clone(),
+            computer_ships: self.computer_ships.clone(),
+            player_shots: shots_by(Player::Human),
+            computer_shots: shots_by(Player::Computer),
+            is_player_turn: played.last().is_none_or(|turn| turn.shooter() == Player::Computer),
+            // Nothing random happens while a replay is watched
+            rng: MatchRng::new(0),
+        })
+    }
+
+    // Same layout as save files, with one line per turn:
+    //
+    //   turn Human 3,4:Miss
+    //   turn Computer 5,5:Hit:Carrier 5,6:Sunk:Carrier
+    pub fn to_replay_string(&self) -> String {
+        let mut lines = vec![format!("{} {}", REPLAY_HEADER, REPLAY_VERSION)];
+        lines.extend(rules_lines(&self.rules));
+        lines.push(match self.difficulty {
+            Some(difficulty) => format!("difficulty {:?}", difficulty),
+            None => "difficulty none".to_string(),
+        });
+        lines.extend(self.player_ships.iter().map(|&ship| ship_line("player_ship", ship)));
+        lines.extend(self.computer_ships.iter().map(|&ship| ship_line("computer_ship", ship)));
+        for turn in &self.turns {
+            let shots = join(
+                turn.shots()
+                    .iter()
+                    .map(|&(row, col, result)| format!("{},{}:{}", row, col, format_result(result))),
+            );
+            lines.push(format!("turn {:?} {}", turn.shooter(), shots));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    // Rejects replays whose recorded answers do not match their own fleets
+    pub fn from_replay_string(text: &str) -> Result<Self, SaveError> {
+        let fields = Fields::parse(text, REPLAY_HEADER, REPLAY_VERSION)?;
+
+        let replay = Self {
+            rules: fields.rules()?,
+            difficulty: match fields.get("difficulty")? {
+                "none" => None,
+                name => Some(parse_name(&Difficulty::ALL, name)?),
+            },
+            player_ships: fields.ships("player_ship")?,
+            computer_ships: fields.ships("computer_ship")?,
+            turns: fields.all("turn").map(parse_turn).collect::<Result<_, _>>()?,
+        };
+
+        if !replay.matches_its_fleets() {
+            return Err(SaveError::Corrupt("turns do not match the fleets".to_string()));
+        }
+        Ok(replay)
+    }
+
+    // Whether playing the turns against the fleets gives the answers recorded for them
+    fn matches_its_fleets(&self) -> bool {
+        self.game_at(self.turns.len()).is_some_and(|game| game.turns() == self.turns())
+    }
+}
+
+pub fn save_replay(replay: &Replay, path: &Path) -> Result<(), SaveError> {
+    write_file(path, &replay.to_replay_string())
+}
+
+pub fn load_replay(path: &Path) -> Result<Replay, SaveError> {
+    Replay::from_replay_string(&fs::read_to_string(path)?)
+}
+
+fn parse_turn(value: &str) -> Result<Turn, SaveError> {
+    let mut parts = value.split_whitespace();
+    let shooter = parse_name(&[Player::Human, Player::Computer], parts.next().unwrap_or_default())?;
+    let shots = parts.map(parse_shot).collect::<Result<_, _>>()?;
+    Ok(Turn::new(shooter, shots))
+}
+
+// "row,col:Miss", "row,col:Hit:Ship" or "row,col:Sunk:Ship"
+fn parse_shot(shot: &str) -> Result<(usize, usize, ShotResult), SaveError> {
+    let (cell, result) = shot.split_once(':').ok_or_else(|| corrupt(shot))?;
+    let (row, col) = parse_cell(cell)?;
+    Ok((row, col, parse_result(result)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::LayoutStyle;
+    use crate::rules::GameMode;
+    use crate::ship::{Orientation, ShipType};
+
+    // A whole seeded match between the Medium AI and the Hard AI in the player's seat
+    fn finished_match(rules: RuleSet) -> Game {
+        let mut game = Game::new(rules, Difficulty::Medium)
+            .with_player_strategy(Difficulty::Hard.strategy())
+            .with_seed(3);
+        assert!(game.place_player_ships(LayoutStyle::Random) && game.place_computer_ships(LayoutStyle::Random));
+        while game.winner().is_none() {
+            let played = if game.is_player_turn() { game.player_turn() } else { game.computer_turn() };
+            played.unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn replay_round_trips() {
+        for rules in [RuleSet::classic(), RuleSet::russian().with_mode(GameMode::Salvo).with_no_touching(true)] {
+            let game = finished_match(rules);
+            let replay = Replay::from_game(&game);
+            let text = replay.to_replay_string();
+
+            assert_eq!(Replay::from_replay_string(&text).unwrap(), replay);
+            assert_eq!(replay.turns(), game.turns());
+        }
+    }
+
+    #[test]
+    fn game_at_steps_through_the_turns() {
+        let replay = Replay::from_game(&finished_match(RuleSet::classic()));
+
+        assert!(replay.game_at(0).unwrap().turns().is_empty());
+        assert_eq!(replay.game_at(3).unwrap().turns(), &replay.turns()[..3]);
+        let last = replay.game_at(replay.turns().len()).unwrap();
+        assert!(last.winner().is_some());
+    }
+
+    #[test]
+    fn networked_match_is_replayed_from_the_revealed_fleet() {
+        let rules = RuleSet::new("Tiny", vec![ShipType::Destroyer]);
+        let mut game = Game::networked(rules, true);
+        game.place_player_ship(ShipType::Destroyer, (0, 0), Orientation::Horizontal).unwrap();
+        assert!(game.record_player_results(&[(2, 2)], &[ShotResult::Hit(ShipType::Destroyer)]));
+        game.fire(Player::Computer, (5, 5)).unwrap();
+        assert!(game.record_player_results(&[(2, 3)], &[ShotResult::Sunk(ShipType::Destroyer)]));
+
+        let honest = FleetReveal::from_parts("00".to_string(), vec![(ShipType::Destroyer, 2, 2, true)]);
+        let replay = Replay::from_networked_game(&game, &honest).unwrap();
+        assert_eq!(Replay::from_replay_string(&replay.to_replay_string()).unwrap(), replay);
+        assert_eq!(replay.game_at(replay.turns().len()).unwrap().winner(), Some(Player::Human));
+
+        let moved = FleetReveal::from_parts("00".to_string(), vec![(ShipType::Destroyer, 7, 7, true)]);
+        assert!(Replay::from_networked_game(&game, &moved).is_none());
+    }
+
+    #[test]
+    fn rejects_answers_that_do_not_match_the_fleets() {
+        let text = Replay::from_game(&finished_match(RuleSet::classic())).to_replay_string();
+
+        // Turn the first miss into a hit on a ship that was not there
+        let line = text.lines().find(|line| line.starts_with("turn") && line.ends_with(":Miss")).unwrap();
+        let forged = text.replacen(line, &line.replace(":Miss", ":Hit:Carrier"), 1);
+        assert!(matches!(Replay::from_replay_string(&forged), Err(SaveError::Corrupt(_))));
+    }
+}
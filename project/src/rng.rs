@@ -0,0 +1,59 @@
+use rand::{Error, RngCore};
+
+// SplitMix64, the random number generator every match owns. Its whole state is one
+// number, so a saved match resumes with exactly the random numbers it would have drawn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchRng {
+    seed: u64,
+    state: u64,
+}
+
+impl MatchRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    // Continues a generator from a saved state
+    pub fn from_state(seed: u64, state: u64) -> Self {
+        Self { seed, state }
+    }
+
+    // The seed the match started with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl RngCore for MatchRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::ship::ShipType;
+
+pub const DEFAULT_BOARD_SIZE: usize = 10;
+pub const MIN_BOARD_SIZE: usize = 5;
+pub const MAX_BOARD_SIZE: usize = 20;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GameMode {
+    Classic, // One shot per turn
+    Salvo,   // One shot per ship still afloat, results revealed together
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Classic, GameMode::Salvo];
+}
+
+// Describes the board and the ships each side has to place. Everything that depends
+// on them (the placement list, computer placement, the win condition) comes from here.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleSet {
+    name: String,
+    width: usize,
+    height: usize,
+    fleet: Vec<ShipType>,
+    mode: GameMode,
+    // Ships may not touch each other, not even diagonally
+    no_touching: bool,
+}
+
+impl RuleSet {
+    pub fn new(name: &str, fleet: Vec<ShipType>) -> Self {
+        Self {
+            name: name.to_string(),
+            width: DEFAULT_BOARD_SIZE,
+            height: DEFAULT_BOARD_SIZE,
+            fleet,
+            mode: GameMode::Classic,
+            no_touching: false,
+        }
+    }
+
+    // Same fleet on a board of `width` columns by `height` rows,
+    // clamped to MIN_BOARD_SIZE..=MAX_BOARD_SIZE
+    pub fn with_board_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        self.height = height.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: GameMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_no_touching(mut self, no_touching: bool) -> Self {
+        self.no_touching = no_touching;
+        self
+    }
+
+    // Hasbro's 2002 edition, the fleet this game always used
+    pub fn classic() -> Self {
+        Self::new(
+            "Classic",
+            vec![
+                ShipType::Carrier,
+                ShipType::Battleship,
+                ShipType::Cruiser,
+                ShipType::Submarine,
+                ShipType::Destroyer,
+            ],
+        )
+    }
+
+    // Morskoy boy: one 4, two 3s, three 2s and four single-cell boats
+    pub fn russian() -> Self {
+        Self::new(
+            "Russian",
+            vec![
+                ShipType::Battleship,
+                ShipType::Cruiser,
+                ShipType::Cruiser,
+                ShipType::Destroyer,
+                ShipType::Destroyer,
+                ShipType::Destroyer,
+                ShipType::TorpedoBoat,
+                ShipType::TorpedoBoat,
+                ShipType::TorpedoBoat,
+                ShipType::TorpedoBoat,
+            ],
+        )
+    }
+
+    // Milton Bradley's 1990 box calls its three-cell surface ship a destroyer and
+    // has a two-cell patrol boat instead of the destroyer
+    pub fn milton_bradley_1990() -> Self {
+        Self::new(
+            "Milton Bradley 1990",
+            vec![
+                ShipType::Carrier,
+                ShipType::Battleship,
+                ShipType::Cruiser,
+                ShipType::Submarine,
+                ShipType::PatrolBoat,
+            ],
+        )
+    }
+
+    pub fn presets() -> Vec<RuleSet> {
+        vec![Self::classic(), Self::russian(), Self::milton_bradley_1990()]
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn no_touching(&self) -> bool {
+        self.no_touching
+    }
+
+    pub fn fleet(&self) -> &[ShipType] {
+        &self.fleet
+    }
+
+    // Number of hits needed to sink the whole fleet
+    pub fn win_condition(&self) -> usize {
+        self.fleet.iter().map(|ship| ship.size()).sum()
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The ship sizes of a fleet, largest first
+    fn sizes(rules: &RuleSet) -> Vec<usize> {
+        let mut sizes: Vec<_> = rules.fleet().iter().map(|ship| ship.size()).collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+
+    #[test]
+    fn classic_is_the_hasbro_fleet() {
+        let rules = RuleSet::classic();
+        assert_eq!(sizes(&rules), [5, 4, 3, 3, 2]);
+        assert_eq!(rules.win_condition(), 17);
+    }
+
+    #[test]
+    fn russian_is_four_three_three_and_the_small_boats() {
+        let rules = RuleSet::russian();
+        assert_eq!(sizes(&rules), [4, 3, 3, 2, 2, 2, 1, 1, 1, 1]);
+        assert_eq!(rules.win_condition(), 20);
+    }
+
+    #[test]
+    fn milton_bradley_1990_has_a_patrol_boat_for_a_destroyer() {
+        let rules = RuleSet::milton_bradley_1990();
+        assert_eq!(sizes(&rules), [5, 4, 3, 3, 2]);
+        assert_eq!(rules.win_condition(), 17);
+        assert!(rules.fleet().contains(&ShipType::PatrolBoat));
+        assert!(!rules.fleet().contains(&ShipType::Destroyer));
+    }
+
+    #[test]
+    fn presets_have_different_names() {
+        let presets = RuleSet::presets();
+        assert_eq!(presets.len(), 3);
+        for (i, rules) in presets.iter().enumerate() {
+            assert!(presets[..i].iter().all(|other| other.name() != rules.name()));
+        }
+    }
+}
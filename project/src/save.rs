@@ -0,0 +1,377 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::ai::Difficulty;
+use crate::board::{Board, ShotResult};
+use crate::game::Game;
+use crate::rng::MatchRng;
+use crate::rules::{GameMode, RuleSet, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::ship::ShipType;
+
+// Bump whenever the format changes. Older saves are rejected rather than misread.
+pub const SAVE_VERSION: u32 = 1;
+const SAVE_HEADER: &str = "battleship-save";
+
+// A ship's type, bow row, bow column and whether it is horizontal
+pub type ShipPlacement = (ShipType, usize, usize, bool);
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    UnsupportedVersion(u32),
+    Corrupt(String),
+    // The computer plays a strategy that is not one of the difficulties
+    CustomStrategy,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "{}", error),
+            SaveError::UnsupportedVersion(version) => write!(f, "unsupported file version {}", version),
+            SaveError::Corrupt(reason) => write!(f, "corrupt file: {}", reason),
+            SaveError::CustomStrategy => write!(f, "matches against a custom strategy cannot be saved"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+// Everything a save file holds. The boards are stored as their ships and the shots
+// fired at them, and the rest of the match is rebuilt from that on loading.
+pub(crate) struct SaveData {
+    pub(crate) rules: RuleSet,
+    pub(crate) difficulty: Option<Difficulty>,
+    pub(crate) player_ships: Vec<ShipPlacement>,
+    pub(crate) computer_ships: Vec<ShipPlacement>,
+    // Cells fired at by each side, in order
+    pub(crate) player_shots: Vec<(usize, usize)>,
+    pub(crate) computer_shots: Vec<(usize, usize)>,
+    pub(crate) is_player_turn: bool,
+    pub(crate) rng: MatchRng,
+}
+
+// A line based text format, one "key value" pair per line after the version header:
+//
+//   battleship-save 1
+//   rules Classic
+//   size 10 10
+//   player_ship Carrier 0 0 horizontal
+//   player_shots 3,4 5,6
+//   ...
+pub fn to_save_string(game: &Game) -> Result<String, SaveError> {
+    let difficulty = game.difficulty().ok_or(SaveError::CustomStrategy)?;
+
+    let mut lines = vec![format!("{} {}", SAVE_HEADER, SAVE_VERSION)];
+    lines.extend(rules_lines(game.rules()));
+    lines.push(format!("difficulty {:?}", difficulty));
+    lines.push(format!("player_turn {}", game.is_player_turn()));
+    lines.push(format!("rng {} {}", game.rng().seed(), game.rng().state()));
+    lines.extend(ship_lines("player_ship", game.player_board()));
+    lines.extend(ship_lines("computer_ship", game.computer_board()));
+    // The player fires at the computer's board and the other way round
+    lines.push(format!("player_shots {}", shot_list(game.computer_board())));
+    lines.push(format!("computer_shots {}", shot_list(game.player_board())));
+
+    Ok(lines.join("\n") + "\n")
+}
+
+pub fn from_save_string(text: &str) -> Result<Game, SaveError> {
+    let fields = Fields::parse(text, SAVE_HEADER, SAVE_VERSION)?;
+
+    let rng = parse_numbers(fields.get("rng")?)?;
+    if rng.len() != 2 {
+        return Err(corrupt(fields.get("rng")?));
+    }
+
+    let save = SaveData {
+        rules: fields.rules()?,
+        difficulty: Some(parse_name(&Difficulty::ALL, fields.get("difficulty")?)?),
+        player_ships: fields.ships("player_ship")?,
+        computer_ships: fields.ships("computer_ship")?,
+        player_shots: parse_cells(fields.get("player_shots")?)?,
+        computer_shots: parse_cells(fields.get("computer_shots")?)?,
+        is_player_turn: parse_value(fields.get("player_turn")?)?,
+        rng: MatchRng::from_state(rng[0], rng[1]),
+    };
+    Game::restore(save).ok_or_else(|| SaveError::Corrupt("ships or shots do not fit the rules".to_string()))
+}
+
+// Writes through a temporary file so a crash halfway never leaves a broken save behind
+pub fn save_game(game: &Game, path: &Path) -> Result<(), SaveError> {
+    write_file(path, &to_save_string(game)?)
+}
+
+pub fn load_game(path: &Path) -> Result<Game, SaveError> {
+    from_save_string(&fs::read_to_string(path)?)
+}
+
+pub(crate) fn write_file(path: &Path, text: &str) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, text)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// The "key value" lines of a file after its version header
+pub(crate) struct Fields<'a> {
+    lines: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Fields<'a> {
+    pub(crate) fn parse(text: &'a str, header: &str, version: u32) -> Result<Self, SaveError> {
+        let mut lines = text.lines();
+        let found = lines
+            .next()
+            .and_then(|line| line.strip_prefix(header))
+            .and_then(|found| found.trim().parse().ok())
+            .ok_or_else(|| SaveError::Corrupt(format!("not a {} file", header)))?;
+        if found != version {
+            return Err(SaveError::UnsupportedVersion(found));
+        }
+
+        Ok(Self {
+            lines: lines.map(|line| line.split_once(' ').unwrap_or((line, ""))).collect(),
+        })
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Result<&'a str, SaveError> {
+        self.all(key).next().ok_or_else(|| SaveError::Corrupt(format!("missing {}", key)))
+    }
+
+    // Values of every line with `key`, for keys that repeat
+    pub(crate) fn all<'k>(&'k self, key: &'k str) -> impl Iterator<Item = &'a str> + 'k {
+        self.lines.iter().filter(move |(k, _)| *k == key).map(|(_, value)| *value)
+    }
+
+    pub(crate) fn ships(&self, key: &str) -> Result<Vec<ShipPlacement>, SaveError> {
+        self.all(key).map(parse_ship).collect()
+    }
+
+    pub(crate) fn rules(&self) -> Result<RuleSet, SaveError> {
+        // Sizes the rules would clamp are refused, so a save never loads with other rules
+        let size = parse_numbers(self.get("size")?)?;
+        let in_range = |side: &u64| (MIN_BOARD_SIZE as u64..=MAX_BOARD_SIZE as u64).contains(side);
+        if size.len() != 2 || !size.iter().all(in_range) {
+            return Err(corrupt(self.get("size")?));
+        }
+        let fleet = self
+            .get("fleet")?
+            .split_whitespace()
+            .map(|name| parse_name(&ShipType::ALL, name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RuleSet::new(self.get("rules")?, fleet)
+            .with_board_size(size[0] as usize, size[1] as usize)
+            .with_mode(parse_name(&GameMode::ALL, self.get("mode")?)?)
+            .with_no_touching(parse_value(self.get("no_touching")?)?))
+    }
+}
+
+pub(crate) fn rules_lines(rules: &RuleSet) -> Vec<String> {
+    vec![
+        format!("rules {}", rules.name()),
+        format!("size {} {}", rules.width(), rules.height()),
+        format!("mode {:?}", rules.mode()),
+        format!("no_touching {}", rules.no_touching()),
+        format!("fleet {}", join(rules.fleet().iter().map(|ship| format!("{:?}", ship)))),
+    ]
+}
+
+pub(crate) fn placements(board: &Board) -> Vec<ShipPlacement> {
+    board
+        .ships()
+        .iter()
+        .map(|ship| {
+            let (row, col) = ship.cells()[0];
+            (ship.ship_type(), row, col, ship.is_horizontal())
+        })
+        .collect()
+}
+
+pub(crate) fn ship_line(key: &str, (ship, row, col, horizontal): ShipPlacement) -> String {
+    let orientation = if horizontal { "horizontal" } else { "vertical" };
+    format!("{} {:?} {} {} {}", key, ship, row, col, orientation)
+}
+
+fn ship_lines(key: &str, board: &Board) -> Vec<String> {
+    placements(board).into_iter().map(|placement| ship_line(key, placement)).collect()
+}
+
+pub(crate) fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(" ")
+}
+
+fn shot_list(board: &Board) -> String {
+    join(board.shots().iter().map(|(row, col)| format!("{},{}", row, col)))
+}
+
+pub(crate) fn corrupt(value: &str) -> SaveError {
+    SaveError::Corrupt(format!("unexpected value {:?}", value))
+}
+
+pub(crate) fn parse_value<T: FromStr>(value: &str) -> Result<T, SaveError> {
+    value.parse().map_err(|_| corrupt(value))
+}
+
+fn parse_numbers(value: &str) -> Result<Vec<u64>, SaveError> {
+    value.split_whitespace().map(parse_value).collect()
+}
+
+// Enum values are saved under their Debug names
+pub(crate) fn parse_name<T: Copy + fmt::Debug>(all: &[T], name: &str) -> Result<T, SaveError> {
+    all.iter().copied().find(|item| format!("{:?}", item) == name).ok_or_else(|| corrupt(name))
+}
+
+fn parse_ship(value: &str) -> Result<ShipPlacement, SaveError> {
+    match value.split_whitespace().collect::<Vec<_>>()[..] {
+        [name, row, col, orientation] => Ok((
+            parse_name(&ShipType::ALL, name)?,
+            parse_value(row)?,
+            parse_value(col)?,
+            match orientation {
+                "horizontal" => true,
+                "vertical" => false,
+                _ => return Err(corrupt(orientation)),
+            },
+        )),
+        _ => Err(corrupt(value)),
+    }
+}
+
+// "Ship,row,col,horizontal" or "Ship,row,col,vertical", as ships are sent over the wire
+pub(crate) fn format_placement((ship, row, col, horizontal): ShipPlacement) -> String {
+    let orientation = if horizontal { "horizontal" } else { "vertical" };
+    format!("{:?},{},{},{}", ship, row, col, orientation)
+}
+
+pub(crate) fn parse_placement(value: &str) -> Result<ShipPlacement, SaveError> {
+    parse_ship(&value.replace(',', " "))
+}
+
+// "row,col" pairs separated by spaces
+pub(crate) fn parse_cells(value: &str) -> Result<Vec<(usize, usize)>, SaveError> {
+    value.split_whitespace().map(parse_cell).collect()
+}
+
+pub(crate) fn parse_cell(cell: &str) -> Result<(usize, usize), SaveError> {
+    let (row, col) = cell.split_once(',').ok_or_else(|| corrupt(cell))?;
+    Ok((parse_value(row)?, parse_value(col)?))
+}
+
+// "Miss", "Hit:Ship" or "Sunk:Ship"
+pub(crate) fn format_result(result: ShotResult) -> String {
+    match result {
+        ShotResult::Miss => "Miss".to_string(),
+        ShotResult::Hit(ship) => format!("Hit:{:?}", ship),
+        ShotResult::Sunk(ship) => format!("Sunk:{:?}", ship),
+    }
+}
+
+pub(crate) fn parse_result(value: &str) -> Result<ShotResult, SaveError> {
+    let mut parts = value.split(':');
+    let result = match (parts.next(), parts.next().map(|name| parse_name(&ShipType::ALL, name))) {
+        (Some("Miss"), None) => ShotResult::Miss,
+        (Some("Hit"), Some(ship)) => ShotResult::Hit(ship?),
+        (Some("Sunk"), Some(ship)) => ShotResult::Sunk(ship?),
+        _ => return Err(corrupt(value)),
+    };
+    match parts.next() {
+        None => Ok(result),
+        Some(_) => Err(corrupt(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::LayoutStyle;
+    use crate::game::Player;
+
+    // A seeded match a few turns in, with both fleets placed
+    fn match_in_progress(rules: RuleSet) -> Game {
+        let mut game = Game::new(rules, Difficulty::Hard).with_seed(7);
+        assert!(game.place_player_ships(LayoutStyle::Random) && game.place_computer_ships(LayoutStyle::Random));
+        let width = game.rules().width();
+        let mut cells = (0..game.rules().height()).flat_map(|row| (0..width).map(move |col| (row, col)));
+        for _ in 0..5 {
+            let targets: Vec<_> = cells.by_ref().take(game.shots_left(Player::Human)).collect();
+            game.fire_salvo(Player::Human, &targets).unwrap();
+            game.computer_turn().unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn save_round_trips() {
+        for rules in [RuleSet::classic(), RuleSet::russian().with_mode(GameMode::Salvo).with_no_touching(true)] {
+            let game = match_in_progress(rules);
+            let text = to_save_string(&game).unwrap();
+            let restored = from_save_string(&text).unwrap();
+
+            assert_eq!(to_save_string(&restored).unwrap(), text);
+            assert_eq!(restored.turns(), game.turns());
+            assert_eq!(restored.rng(), game.rng());
+            assert_eq!(restored.hits(Player::Computer), game.hits(Player::Computer));
+        }
+    }
+
+    #[test]
+    fn restored_match_plays_on_the_same() {
+        let mut game = match_in_progress(RuleSet::classic());
+        let mut restored = from_save_string(&to_save_string(&game).unwrap()).unwrap();
+
+        game.fire(Player::Human, (9, 9)).unwrap();
+        restored.fire(Player::Human, (9, 9)).unwrap();
+        assert_eq!(restored.computer_turn().unwrap(), game.computer_turn().unwrap());
+    }
+
+    #[test]
+    fn rejects_other_versions_and_broken_saves() {
+        let text = to_save_string(&match_in_progress(RuleSet::classic())).unwrap();
+
+        let newer = text.replacen("battleship-save 1", "battleship-save 2", 1);
+        assert!(matches!(from_save_string(&newer), Err(SaveError::UnsupportedVersion(2))));
+
+        let wrong_fleet = text.replacen("computer_ship Carrier", "computer_ship Battleship", 1);
+        assert!(matches!(from_save_string(&wrong_fleet), Err(SaveError::Corrupt(_))));
+
+        let truncated = &text[..text.find("player_shots").unwrap()];
+        assert!(matches!(from_save_string(truncated), Err(SaveError::Corrupt(_))));
+    }
+
+    #[test]
+    fn rejects_board_sizes_the_rules_do_not_allow() {
+        let text = to_save_string(&match_in_progress(RuleSet::classic())).unwrap();
+
+        for size in [MIN_BOARD_SIZE - 1, MAX_BOARD_SIZE + 1] {
+            let resized = text.replacen("size 10 10", &format!("size {} 10", size), 1);
+            assert!(matches!(from_save_string(&resized), Err(SaveError::Corrupt(_))), "{}", size);
+        }
+
+        // The largest board still loads as it was saved
+        let rules = RuleSet::classic().with_board_size(MAX_BOARD_SIZE, 8);
+        let restored = from_save_string(&to_save_string(&match_in_progress(rules.clone())).unwrap()).unwrap();
+        assert_eq!(restored.rules(), &rules);
+    }
+
+    #[test]
+    fn placements_round_trip() {
+        for placement in [(ShipType::Carrier, 0, 3, true), (ShipType::TorpedoBoat, 9, 9, false)] {
+            assert_eq!(parse_placement(&format_placement(placement)).unwrap(), placement);
+        }
+        assert!(parse_placement("Carrier,0,3,diagonal").is_err());
+    }
+}
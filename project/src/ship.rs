@@ -0,0 +1,123 @@
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShipType {
+    TorpedoBoat, // Size 1
+    PatrolBoat,  // Size 2
+    Destroyer,   // Size 2
+    Submarine,   // Size 3
+    Cruiser,     // Size 3
+    Battleship,  // Size 4
+    Carrier,     // Size 5
+}
+
+impl ShipType {
+    pub const ALL: [ShipType; 7] = [
+        ShipType::TorpedoBoat,
+        ShipType::PatrolBoat,
+        ShipType::Destroyer,
+        ShipType::Submarine,
+        ShipType::Cruiser,
+        ShipType::Battleship,
+        ShipType::Carrier,
+    ];
+
+    pub fn size(&self) -> usize {
+        match self {
+            ShipType::TorpedoBoat => 1,
+            ShipType::PatrolBoat => 2,
+            ShipType::Destroyer => 2,
+            ShipType::Submarine => 3,
+            ShipType::Cruiser => 3,
+            ShipType::Battleship => 4,
+            ShipType::Carrier => 5,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    // The bow is the leftmost cell
+    Horizontal,
+    // The bow is the topmost cell
+    Vertical,
+}
+
+impl Orientation {
+    pub fn from_horizontal(horizontal: bool) -> Self {
+        if horizontal {
+            Orientation::Horizontal
+        } else {
+            Orientation::Vertical
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        *self == Orientation::Horizontal
+    }
+
+    pub fn flipped(&self) -> Self {
+        Self::from_horizontal(!self.is_horizontal())
+    }
+}
+
+// Which of a board's ships a placement put there: its index in `Board::ships`, which
+// only changes when a ship before it is taken off again
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ShipId(usize);
+
+impl ShipId {
+    pub(crate) fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+// A ship that has been put on a board, together with the damage it has taken
+#[derive(Clone, Debug)]
+pub struct PlacedShip {
+    ship_type: ShipType,
+    cells: Vec<(usize, usize)>,
+    hits: usize,
+}
+
+impl PlacedShip {
+    pub fn new(ship_type: ShipType, cells: Vec<(usize, usize)>) -> Self {
+        Self {
+            ship_type,
+            cells,
+            hits: 0,
+        }
+    }
+
+    pub fn ship_type(&self) -> ShipType {
+        self.ship_type
+    }
+
+    // (row, col) of every cell the ship covers, starting from the bow
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    // Single-cell ships count as horizontal
+    pub fn is_horizontal(&self) -> bool {
+        self.cells.len() < 2 || self.cells[0].0 == self.cells[1].0
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_horizontal(self.is_horizontal())
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn is_sunk(&self) -> bool {
+        self.hits >= self.cells.len()
+    }
+
+    pub(crate) fn take_hit(&mut self) {
+        self.hits += 1;
+    }
+}
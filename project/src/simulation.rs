@@ -0,0 +1,366 @@
+// Matches between two computer players with no one watching, for comparing strategies
+// and fleet layouts over many games. They play under the same rules as a normal
+// match, with one of them taking the player's seat.
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use crate::ai::{Difficulty, ShotStrategy};
+use crate::board::LayoutStyle;
+use crate::bot::{BotError, ExternalBot};
+use crate::fleet::Fleet;
+use crate::game::{Game, Player, ShotError};
+use crate::rules::RuleSet;
+
+// Why a simulated match could not be played to the end
+#[derive(Debug)]
+pub enum SimulationError {
+    // An external bot could not be started or laid out an illegal fleet
+    Bot(BotError),
+    // No layout of the rules' fleet was found on their board
+    FleetDoesNotFit(String),
+    // A strategy picked a volley the rules do not allow
+    IllegalVolley(ShotError),
+    // A thread playing matches panicked
+    Panicked,
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulationError::Bot(error) => write!(f, "{}", error),
+            SimulationError::FleetDoesNotFit(rules) => write!(f, "the {} fleet does not fit on its board", rules),
+            SimulationError::IllegalVolley(error) => write!(f, "a strategy picked an illegal volley: {}", error),
+            SimulationError::Panicked => write!(f, "a simulation thread panicked"),
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+impl From<BotError> for SimulationError {
+    fn from(error: BotError) -> Self {
+        SimulationError::Bot(error)
+    }
+}
+
+impl From<ShotError> for SimulationError {
+    fn from(error: ShotError) -> Self {
+        SimulationError::IllegalVolley(error)
+    }
+}
+
+// One of the two computer players: how it shoots and how it lays out its fleet
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Contestant {
+    shooter: Shooter,
+    layout: LayoutStyle,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Shooter {
+    Builtin(Difficulty),
+    // An external bot's program and its arguments, started afresh for every match
+    External(String),
+}
+
+impl Contestant {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            shooter: Shooter::Builtin(difficulty),
+            layout: LayoutStyle::Random,
+        }
+    }
+
+    // An external bot, which also lays out its own fleet
+    pub fn external(command: &str) -> Self {
+        Self {
+            shooter: Shooter::External(command.to_string()),
+            layout: LayoutStyle::Random,
+        }
+    }
+
+    pub fn with_layout(mut self, layout: LayoutStyle) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    // None for an external bot
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        match self.shooter {
+            Shooter::Builtin(difficulty) => Some(difficulty),
+            Shooter::External(_) => None,
+        }
+    }
+
+    pub fn command(&self) -> Option<&str> {
+        match &self.shooter {
+            Shooter::Builtin(_) => None,
+            Shooter::External(command) => Some(command),
+        }
+    }
+
+    pub fn layout(&self) -> LayoutStyle {
+        self.layout
+    }
+
+    // How the contestant shoots, and its fleet if it lays that out itself
+    fn join(&self, rules: &RuleSet) -> Result<(Box<dyn ShotStrategy>, Option<Fleet>), BotError> {
+        match &self.shooter {
+            Shooter::Builtin(difficulty) => Ok((difficulty.strategy(), None)),
+            Shooter::External(command) => {
+                let mut bot = ExternalBot::launch(command, rules)?;
+                let fleet = bot.place_fleet()?;
+                Ok((Box::new(bot), Some(fleet)))
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Side {
+    First,
+    Second,
+}
+
+// How one simulated match went
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MatchReport {
+    winner: Side,
+    // Whether the winner fired the first volley of the match
+    winner_started: bool,
+    // Shots the winner needed to sink the whole fleet, or fired before the other side forfeited
+    shots: usize,
+    turns: usize,
+    // Whether the loser's external bot failed during the match instead of its fleet being sunk
+    forfeit: bool,
+}
+
+impl MatchReport {
+    pub fn winner(&self) -> Side {
+        self.winner
+    }
+
+    pub fn winner_started(&self) -> bool {
+        self.winner_started
+    }
+
+    pub fn shots(&self) -> usize {
+        self.shots
+    }
+
+    pub fn turns(&self) -> usize {
+        self.turns
+    }
+
+    pub fn is_forfeit(&self) -> bool {
+        self.forfeit
+    }
+}
+
+// Plays one match to the end. `first` starts on even seeds and `second` on odd ones,
+// so neither side gets the first volley in every match of a run.
+pub fn play_match(
+    rules: &RuleSet,
+    first: &Contestant,
+    second: &Contestant,
+    seed: u64,
+) -> Result<MatchReport, SimulationError> {
+    // The contestant in the player's seat always shoots first
+    let first_starts = seed.is_multiple_of(2);
+    let (starter, other) = if first_starts { (first, second) } else { (second, first) };
+    let (starter_strategy, starter_fleet) = starter.join(rules)?;
+    let (other_strategy, other_fleet) = other.join(rules)?;
+
+    let mut game = Game::with_strategy(rules.clone(), other_strategy)
+        .with_player_strategy(starter_strategy)
+        .with_seed(seed);
+    let placed = match starter_fleet {
+        Some(fleet) => game.set_player_fleet(fleet),
+        None => game.place_player_ships(starter.layout()),
+    } && match other_fleet {
+        Some(fleet) => game.set_computer_fleet(fleet),
+        None => game.place_computer_ships(other.layout()),
+    };
+    if !placed {
+        return Err(SimulationError::FleetDoesNotFit(rules.name().to_string()));
+    }
+
+    while game.winner().is_none() {
+        let played = if game.is_player_turn() { game.player_turn() } else { game.computer_turn() };
+        match played {
+            Ok(_) | Err(ShotError::Forfeited) => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    let winner = game.winner().unwrap_or(Player::Human);
+    let winner_started = winner == Player::Human;
+    Ok(MatchReport {
+        winner: if winner_started == first_starts { Side::First } else { Side::Second },
+        winner_started,
+        shots: game.shots_fired(winner),
+        turns: game.turns().len(),
+        forfeit: game.forfeit().is_some(),
+    })
+}
+
+// Plays `games` matches, the n-th with seed `seed + n`, spread over `threads` threads.
+// The reports come back in seed order, so a run gives the same results on any number
+// of threads. Once a match fails no more are started, and the error of the match that
+// failed first is returned. A thread that panicked only counts if no match failed.
+pub fn simulate(
+    rules: &RuleSet,
+    first: &Contestant,
+    second: &Contestant,
+    games: usize,
+    seed: u64,
+    threads: usize,
+) -> Result<Vec<MatchReport>, SimulationError> {
+    let threads = threads.clamp(1, games.max(1));
+    let per_thread = games.div_ceil(threads);
+    // The first failure of the run, which also stops the other threads
+    let failure = &Mutex::new(None);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let start = worker * per_thread;
+                let end = (start + per_thread).min(games);
+                scope.spawn(move || {
+                    let mut reports = Vec::with_capacity(end.saturating_sub(start));
+                    for n in start..end {
+                        if lock(failure).is_some() {
+                            break;
+                        }
+                        match play_match(rules, first, second, seed.wrapping_add(n as u64)) {
+                            Ok(report) => reports.push(report),
+                            Err(error) => {
+                                lock(failure).get_or_insert(error);
+                                break;
+                            }
+                        }
+                    }
+                    reports
+                })
+            })
+            .collect();
+
+        let mut reports = Vec::with_capacity(games);
+        for worker in workers {
+            match worker.join() {
+                Ok(played) => reports.extend(played),
+                Err(_) => {
+                    lock(failure).get_or_insert(SimulationError::Panicked);
+                }
+            }
+        }
+        match lock(failure).take() {
+            Some(error) => Err(error),
+            None => Ok(reports),
+        }
+    })
+}
+
+// The failure is only ever set, so a thread that panicked while holding the lock
+// cannot have left it half written
+fn lock(failure: &Mutex<Option<SimulationError>>) -> MutexGuard<'_, Option<SimulationError>> {
+    failure.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Shots to win over a set of matches, e.g. those one side won
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShotStats {
+    // Sorted, lowest first
+    shots: Vec<usize>,
+}
+
+impl ShotStats {
+    pub fn new(mut shots: Vec<usize>) -> Self {
+        shots.sort_unstable();
+        Self { shots }
+    }
+
+    // The shots to win of every match `side` won by sinking the whole fleet
+    pub fn for_side(reports: &[MatchReport], side: Side) -> Self {
+        let sunk = reports.iter().filter(|report| report.winner() == side && !report.is_forfeit());
+        Self::new(sunk.map(MatchReport::shots).collect())
+    }
+
+    pub fn count(&self) -> usize {
+        self.shots.len()
+    }
+
+    pub fn min(&self) -> Option<usize> {
+        self.shots.first().copied()
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.shots.last().copied()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.shots.is_empty() {
+            return None;
+        }
+        Some(self.shots.iter().sum::<usize>() as f64 / self.shots.len() as f64)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+
+    // Interpolated between the two nearest matches, with `percent` from 0 to 100
+    pub fn percentile(&self, percent: f64) -> Option<f64> {
+        let last = self.shots.len().checked_sub(1)?;
+        let rank = percent.clamp(0.0, 100.0) / 100.0 * last as f64;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+        let fraction = rank - below as f64;
+        Some(self.shots[below] as f64 * (1.0 - fraction) + self.shots[above] as f64 * fraction)
+    }
+
+    // How many matches fall in each run of `width` shots, as (lowest shots in the
+    // bucket, matches), from the fewest shots up. Buckets in between that no match
+    // fell in are included with a count of 0.
+    pub fn histogram(&self, width: usize) -> Vec<(usize, usize)> {
+        let width = width.max(1);
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return Vec::new();
+        };
+
+        let first = min / width;
+        let mut buckets: Vec<_> = (first..=max / width).map(|bucket| (bucket * width, 0)).collect();
+        for &shots in &self.shots {
+            buckets[shots / width - first].1 += 1;
+        }
+        buckets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ship::ShipType;
+
+    #[test]
+    fn simulate_keeps_seed_order_on_any_number_of_threads() {
+        let rules = RuleSet::classic();
+        let (first, second) = (Contestant::new(Difficulty::Hard), Contestant::new(Difficulty::Easy));
+
+        let alone = simulate(&rules, &first, &second, 12, 5, 1).unwrap();
+        assert_eq!(alone.len(), 12);
+        assert_eq!(simulate(&rules, &first, &second, 12, 5, 4).unwrap(), alone);
+    }
+
+    #[test]
+    fn matches_that_cannot_be_played_fail_instead_of_panicking() {
+        let crowded = RuleSet::new("Crowded", vec![ShipType::Carrier; 6]).with_board_size(5, 5);
+        let easy = Contestant::new(Difficulty::Easy);
+        let result = simulate(&crowded, &easy, &easy, 8, 0, 2);
+        assert!(matches!(result, Err(SimulationError::FleetDoesNotFit(_))));
+
+        let missing = Contestant::external("./no-such-bot");
+        let result = play_match(&RuleSet::classic(), &missing, &easy, 0);
+        assert!(matches!(result, Err(SimulationError::Bot(BotError::Io(_)))));
+    }
+}
@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use ggez::graphics::{Color, Rect, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::{draw_state, draw_text, Action, Align, WidgetState, DISABLED_COLOR};
+
+type Callback<T> = Rc<dyn Fn(&mut T)>;
+
+pub struct Button<T> {
+    label: String,
+    area: Rect,
+    size: f32,
+    color: Color,
+    align: Align,
+    enabled: bool,
+    on_click: Option<Callback<T>>,
+}
+
+impl<T: 'static> Button<T> {
+    pub fn new(label: impl Into<String>, area: Rect) -> Self {
+        Self {
+            label: label.into(),
+            area,
+            size: 32.0,
+            color: Color::from_rgb(255, 255, 0),
+            align: Align::Left,
+            enabled: true,
+            on_click: None,
+        }
+    }
+
+    // Text size in pixels
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    // A disabled button is greyed out and cannot be pressed or focused
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn on_click(mut self, callback: impl Fn(&mut T) + 'static) -> Self {
+        self.on_click = Some(Rc::new(callback));
+        self
+    }
+
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(super) fn action(&self) -> Option<Action<T>> {
+        let callback = self.on_click.clone()?;
+        Some(Box::new(move |target: &mut T| callback(target)))
+    }
+
+    pub(super) fn draw(&self, ctx: &mut Context, state: WidgetState) -> GameResult {
+        draw_state(ctx, self.area, state)?;
+        let color = if self.enabled { self.color } else { DISABLED_COLOR };
+        let text = Text::new(TextFragment::new(self.label.as_str()).color(color).scale(self.size));
+        draw_text(ctx, &text, self.area, self.align)
+    }
+}
@@ -0,0 +1,46 @@
+use ggez::graphics::{Color, Rect, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::{draw_text, Align};
+
+// Text that cannot be used, e.g. a heading or a status line
+pub struct Label {
+    text: String,
+    area: Rect,
+    size: f32,
+    color: Color,
+    align: Align,
+}
+
+impl Label {
+    pub fn new(text: impl Into<String>, area: Rect) -> Self {
+        Self {
+            text: text.into(),
+            area,
+            size: 32.0,
+            color: Color::WHITE,
+            align: Align::Left,
+        }
+    }
+
+    // Text size in pixels
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub(super) fn draw(&self, ctx: &mut Context) -> GameResult {
+        let text = Text::new(TextFragment::new(self.text.as_str()).color(self.color).scale(self.size));
+        draw_text(ctx, &text, self.area, self.align)
+    }
+}
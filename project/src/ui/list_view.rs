@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use ggez::graphics::{Color, Rect, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::{draw_state, draw_text, Action, Align, WidgetState};
+
+type SelectCallback<T> = Rc<dyn Fn(&mut T, usize)>;
+
+// A column of items of which one can be picked
+pub struct ListView<T> {
+    items: Vec<String>,
+    area: Rect,
+    item_height: f32,
+    size: f32,
+    color: Color,
+    on_select: Option<SelectCallback<T>>,
+}
+
+impl<T: 'static> ListView<T> {
+    // `item_height` pixels per item, from the top of `area` down
+    pub fn new(items: Vec<String>, area: Rect, item_height: f32) -> Self {
+        Self {
+            items,
+            area,
+            item_height,
+            size: 32.0,
+            color: Color::from_rgb(200, 200, 200),
+            on_select: None,
+        }
+    }
+
+    // Text size in pixels
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn on_select(mut self, callback: impl Fn(&mut T, usize) + 'static) -> Self {
+        self.on_select = Some(Rc::new(callback));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn item_area(&self, item: usize) -> Rect {
+        Rect::new(self.area.x, self.area.y + item as f32 * self.item_height, self.area.w, self.item_height)
+    }
+
+    pub(super) fn item_at(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.items.len()).find(|&item| self.item_area(item).contains([x, y]))
+    }
+
+    pub(super) fn action(&self, item: usize) -> Option<Action<T>> {
+        let callback = self.on_select.clone()?;
+        Some(Box::new(move |target: &mut T| callback(target, item)))
+    }
+
+    pub(super) fn draw(&self, ctx: &mut Context, state: impl Fn(usize) -> WidgetState) -> GameResult {
+        for (item, label) in self.items.iter().enumerate() {
+            let area = self.item_area(item);
+            draw_state(ctx, area, state(item))?;
+            let text = Text::new(TextFragment::new(label.as_str()).color(self.color).scale(self.size));
+            draw_text(ctx, &text, area, Align::Left)?;
+        }
+        Ok(())
+    }
+}
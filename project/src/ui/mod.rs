@@ -0,0 +1,275 @@
+// A small widget layer for the menus and buttons: labels, buttons, panels and lists
+// that draw themselves, show when they are hovered, pressed or focused, and hand back
+// a callback to run against the game when they are used.
+mod button;
+mod label;
+mod list_view;
+mod panel;
+
+pub use button::Button;
+pub use label::Label;
+pub use list_view::ListView;
+pub use panel::Panel;
+
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::{self, Color, DrawMode, Mesh, Rect, Text};
+use ggez::{Context, GameResult};
+
+const HOVER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.15);
+const PRESSED_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.3);
+const FOCUS_COLOR: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+const DISABLED_COLOR: Color = Color::new(0.6, 0.6, 0.6, 1.0);
+
+// What using a widget does to the state that owns the Ui
+pub type Action<T> = Box<dyn FnOnce(&mut T)>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+}
+
+pub enum Widget<T> {
+    Label(Label),
+    Button(Button<T>),
+    Panel(Panel<T>),
+    List(ListView<T>),
+}
+
+impl<T> From<Label> for Widget<T> {
+    fn from(label: Label) -> Self {
+        Widget::Label(label)
+    }
+}
+
+impl<T> From<Button<T>> for Widget<T> {
+    fn from(button: Button<T>) -> Self {
+        Widget::Button(button)
+    }
+}
+
+impl<T> From<Panel<T>> for Widget<T> {
+    fn from(panel: Panel<T>) -> Self {
+        Widget::Panel(panel)
+    }
+}
+
+impl<T> From<ListView<T>> for Widget<T> {
+    fn from(list: ListView<T>) -> Self {
+        Widget::List(list)
+    }
+}
+
+// How a widget, or one item of a list, is drawn this frame
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct WidgetState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+}
+
+// A widget the pointer or keyboard can be on, with the item for lists
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Target {
+    widget: usize,
+    item: Option<usize>,
+}
+
+// The widgets of the screen being shown. They are kept from frame to frame and only
+// replaced when something they show changes, while the Ui keeps which of them is
+// hovered, pressed and focused.
+pub struct Ui<T> {
+    // Panels come right before the widgets inside them, so they are drawn below them
+    widgets: Vec<Widget<T>>,
+    mouse: (f32, f32),
+    pressed: Option<Target>,
+    // Only set once the keyboard is used, so the focus ring stays out of the way otherwise
+    focused: Option<Target>,
+}
+
+impl<T: 'static> Ui<T> {
+    pub fn new() -> Self {
+        Self {
+            widgets: Vec::new(),
+            mouse: (0.0, 0.0),
+            pressed: None,
+            focused: None,
+        }
+    }
+
+    // Replaces the widgets. A pressed or focused widget stays so if it is still there.
+    pub fn set_widgets(&mut self, widgets: Vec<Widget<T>>) {
+        self.widgets.clear();
+        for widget in widgets {
+            self.add(widget);
+        }
+        self.pressed = self.pressed.filter(|&target| self.is_target(target));
+        self.focused = self.focused.filter(|&target| self.is_target(target));
+    }
+
+    fn add(&mut self, widget: Widget<T>) {
+        match widget {
+            Widget::Panel(mut panel) => {
+                let children = panel.take_children();
+                self.widgets.push(Widget::Panel(panel));
+                for child in children {
+                    self.add(child);
+                }
+            }
+            widget => self.widgets.push(widget),
+        }
+    }
+
+    fn is_target(&self, target: Target) -> bool {
+        match (self.widgets.get(target.widget), target.item) {
+            (Some(Widget::Button(button)), None) => button.is_enabled(),
+            (Some(Widget::List(list)), Some(item)) => item < list.len(),
+            _ => false,
+        }
+    }
+
+    // The topmost widget at (x, y) that can be used
+    fn target_at(&self, x: f32, y: f32) -> Option<Target> {
+        self.widgets.iter().enumerate().rev().find_map(|(index, widget)| match widget {
+            Widget::Button(button) if button.is_enabled() && button.area().contains([x, y]) => {
+                Some(Target { widget: index, item: None })
+            }
+            Widget::List(list) => list.item_at(x, y).map(|item| Target { widget: index, item: Some(item) }),
+            _ => None,
+        })
+    }
+
+    fn action(&self, target: Target) -> Option<Action<T>> {
+        match (self.widgets.get(target.widget)?, target.item) {
+            (Widget::Button(button), None) => button.action(),
+            (Widget::List(list), Some(item)) => list.action(item),
+            _ => None,
+        }
+    }
+
+    pub fn mouse_motion(&mut self, x: f32, y: f32) {
+        self.mouse = (x, y);
+    }
+
+    // Presses the widget under the mouse. False if there is none, so the click is
+    // meant for whatever is below the widgets.
+    pub fn mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.mouse = (x, y);
+        self.focused = None;
+        self.pressed = self.target_at(x, y);
+        self.pressed.is_some()
+    }
+
+    // A widget is used when the button is let go over the same widget it was pressed on
+    pub fn mouse_up(&mut self, x: f32, y: f32) -> Option<Action<T>> {
+        let pressed = self.pressed.take()?;
+        if self.target_at(x, y) != Some(pressed) {
+            return None;
+        }
+        self.action(pressed)
+    }
+
+    // Tab and Shift+Tab move the focus, Up and Down move through a focused list, and
+    // Return or Space use the focused widget
+    pub fn key_down(&mut self, keycode: KeyCode, keymods: KeyMods) -> Option<Action<T>> {
+        match keycode {
+            KeyCode::Tab => {
+                self.move_focus(!keymods.contains(KeyMods::SHIFT));
+                None
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let focused = self.focused?;
+                let Some(Widget::List(list)) = self.widgets.get(focused.widget) else {
+                    return None;
+                };
+                let item = focused.item.unwrap_or(0);
+                let item = if keycode == KeyCode::Up { item.saturating_sub(1) } else { (item + 1).min(list.len() - 1) };
+                self.focused = Some(Target { item: Some(item), ..focused });
+                None
+            }
+            KeyCode::Return | KeyCode::Space => self.action(self.focused?),
+            _ => None,
+        }
+    }
+
+    // Every widget the keyboard can reach, in the order they were added
+    fn focus_order(&self) -> Vec<Target> {
+        self.widgets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, widget)| match widget {
+                Widget::Button(button) if button.is_enabled() => Some(Target { widget: index, item: None }),
+                Widget::List(list) if list.len() > 0 => Some(Target { widget: index, item: Some(0) }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self.focused.and_then(|focused| order.iter().position(|target| target.widget == focused.widget));
+        let next = match current {
+            Some(index) if forward => (index + 1) % order.len(),
+            Some(index) => (index + order.len() - 1) % order.len(),
+            None if forward => 0,
+            None => order.len() - 1,
+        };
+        self.focused = Some(order[next]);
+    }
+
+    fn state(&self, target: Target) -> WidgetState {
+        let (x, y) = self.mouse;
+        let hovered = self.target_at(x, y) == Some(target);
+        WidgetState {
+            hovered,
+            pressed: hovered && self.pressed == Some(target),
+            focused: self.focused == Some(target),
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        for (index, widget) in self.widgets.iter().enumerate() {
+            match widget {
+                Widget::Label(label) => label.draw(ctx)?,
+                Widget::Panel(panel) => panel.draw(ctx)?,
+                Widget::Button(button) => button.draw(ctx, self.state(Target { widget: index, item: None }))?,
+                Widget::List(list) => {
+                    list.draw(ctx, |item| self.state(Target { widget: index, item: Some(item) }))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Draws `text` along the top of `area`
+pub fn draw_text(ctx: &mut Context, text: &Text, area: Rect, align: Align) -> GameResult {
+    let x = match align {
+        Align::Left => area.x,
+        Align::Center => area.x + (area.w - text.width(ctx)) / 2.0,
+    };
+    graphics::draw(ctx, text, graphics::DrawParam::default().dest([x, area.y]))
+}
+
+// The hover and press shading and the focus ring of a button or list item
+pub(crate) fn draw_state(ctx: &mut Context, area: Rect, state: WidgetState) -> GameResult {
+    let shade = if state.pressed {
+        Some(PRESSED_COLOR)
+    } else if state.hovered {
+        Some(HOVER_COLOR)
+    } else {
+        None
+    };
+    if let Some(color) = shade {
+        let background = Mesh::new_rectangle(ctx, DrawMode::fill(), area, color)?;
+        graphics::draw(ctx, &background, graphics::DrawParam::default())?;
+    }
+    if state.focused {
+        let ring = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), area, FOCUS_COLOR)?;
+        graphics::draw(ctx, &ring, graphics::DrawParam::default())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use ggez::graphics::{self, Color, DrawMode, Mesh, Rect};
+use ggez::{Context, GameResult};
+
+use super::Widget;
+
+// A filled box that groups the widgets drawn on top of it
+pub struct Panel<T> {
+    area: Rect,
+    color: Color,
+    border: Option<Color>,
+    children: Vec<Widget<T>>,
+}
+
+impl<T> Panel<T> {
+    pub fn new(area: Rect) -> Self {
+        Self {
+            area,
+            color: Color::from_rgba(0, 0, 0, 160),
+            border: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_border(mut self, color: Color) -> Self {
+        self.border = Some(color);
+        self
+    }
+
+    pub fn with_child(mut self, child: impl Into<Widget<T>>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    pub(super) fn take_children(&mut self) -> Vec<Widget<T>> {
+        std::mem::take(&mut self.children)
+    }
+
+    pub(super) fn draw(&self, ctx: &mut Context) -> GameResult {
+        let background = Mesh::new_rectangle(ctx, DrawMode::fill(), self.area, self.color)?;
+        graphics::draw(ctx, &background, graphics::DrawParam::default())?;
+        if let Some(border) = self.border {
+            let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), self.area, border)?;
+            graphics::draw(ctx, &outline, graphics::DrawParam::default())?;
+        }
+        Ok(())
+    }
+}